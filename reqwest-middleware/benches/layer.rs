@@ -0,0 +1,91 @@
+//! Compares the overhead of a dynamically dispatched `Middleware` stack with the equivalent
+//! statically dispatched `Layer` stack.
+//!
+//! Both stacks end in a middleware answering every request itself, so no request is sent and
+//! only the cost of going through the stack is measured.
+//!
+//! Run with `cargo bench --bench layer`.
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use http::Extensions;
+use reqwest::{header::HeaderValue, Client, Method, Request, Response, Url};
+use reqwest_middleware::{ClientBuilder, Middleware, Next, Result};
+use tower::{util::MapRequestLayer, ServiceExt};
+
+/// The dynamic way: a `Middleware` adding a header.
+struct AddHeader;
+
+#[async_trait::async_trait]
+impl Middleware for AddHeader {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        req.headers_mut()
+            .insert("x-client", HeaderValue::from_static("bench"));
+        next.run(req, extensions).await
+    }
+}
+
+/// The static way: the same header, added by a tower layer.
+fn add_header(mut req: Request) -> Request {
+    req.headers_mut()
+        .insert("x-client", HeaderValue::from_static("bench"));
+    req
+}
+
+/// Answers every request without sending it.
+struct Respond;
+
+#[async_trait::async_trait]
+impl Middleware for Respond {
+    async fn handle(
+        &self,
+        _req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> Result<Response> {
+        Ok(http::Response::new("").into())
+    }
+}
+
+fn dispatch(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let url: Url = "http://localhost".parse().unwrap();
+
+    let dynamic = ClientBuilder::new(Client::new())
+        .with(AddHeader)
+        .with(AddHeader)
+        .with(AddHeader)
+        .with(Respond)
+        .build();
+    c.bench_function("middleware (dynamic dispatch)", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let req = Request::new(Method::GET, url.clone());
+            dynamic.execute(req).await.unwrap()
+        })
+    });
+
+    let layered = ClientBuilder::new(Client::new())
+        .with(Respond)
+        .with_layer(MapRequestLayer::new(add_header))
+        .with_layer(MapRequestLayer::new(add_header))
+        .with_layer(MapRequestLayer::new(add_header))
+        .build();
+    c.bench_function("layer (static dispatch)", |b| {
+        b.to_async(&runtime).iter_batched(
+            || layered.clone(),
+            |service| async {
+                let req = Request::new(Method::GET, url.clone());
+                service.oneshot(req).await.unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+//! Sends the same requests through a dynamically dispatched `Middleware` stack and through a
+//! statically dispatched `Layer` stack, and prints how long each one took.
+//!
+//! The requests go to a local server, so the timings are dominated by the network. The `layer`
+//! bench measures the overhead of the stacks themselves.
+//!
+//! Run with `cargo run --release --example layer`.
+use std::time::{Duration, Instant};
+
+use http::Extensions;
+use reqwest::{header::HeaderValue, Client, Request, Response};
+use reqwest_middleware::{ClientBuilder, Middleware, Next, Result};
+use tower::{util::MapRequestLayer, Service, ServiceExt};
+use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+const REQUESTS: usize = 2_000;
+
+/// The dynamic way: a `Middleware` adding a header.
+struct AddHeader;
+
+#[async_trait::async_trait]
+impl Middleware for AddHeader {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        req.headers_mut()
+            .insert("x-client", HeaderValue::from_static("example"));
+        next.run(req, extensions).await
+    }
+}
+
+/// The static way: the same header, added by a tower layer.
+fn add_header(mut req: Request) -> Request {
+    req.headers_mut()
+        .insert("x-client", HeaderValue::from_static("example"));
+    req
+}
+
+#[tokio::main]
+async fn main() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let url: reqwest::Url = server.uri().parse().unwrap();
+
+    let dynamic = ClientBuilder::new(Client::new())
+        .with(AddHeader)
+        .with(AddHeader)
+        .with(AddHeader)
+        .build();
+
+    let mut layered = ClientBuilder::new(Client::new())
+        .with_layer(MapRequestLayer::new(add_header))
+        .with_layer(MapRequestLayer::new(add_header))
+        .with_layer(MapRequestLayer::new(add_header))
+        .build();
+
+    let start = Instant::now();
+    for _ in 0..REQUESTS {
+        dynamic.get(url.clone()).send().await.unwrap();
+    }
+    report("Middleware (dynamic dispatch)", start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..REQUESTS {
+        let req = Request::new(reqwest::Method::GET, url.clone());
+        layered.ready().await.unwrap().call(req).await.unwrap();
+    }
+    report("Layer (static dispatch)", start.elapsed());
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:<32} {:>8.2?} total, {:>8.2?} per request",
+        name,
+        elapsed,
+        elapsed / REQUESTS as u32
+    );
+}
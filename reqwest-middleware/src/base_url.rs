@@ -0,0 +1,183 @@
+use http::Extensions;
+use reqwest::{Method, Request, Response, Url};
+
+use crate::{ClientWithMiddleware, RequestBuilder, Result};
+
+/// A [`ClientWithMiddleware`] sending requests relative to a base URL, created with
+/// [`ClientWithMiddleware::with_base_url`].
+///
+/// Paths are resolved against the base URL following the usual rules for relative references,
+/// as in [`Url::join`]: the base URL should end with a `/` for its path to be kept, and absolute
+/// URLs are used as-is.
+///
+/// # Example
+///
+/// ```
+/// use reqwest::Url;
+/// use reqwest_middleware::ClientBuilder;
+///
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .build()
+///     .with_base_url(Url::parse("https://api.truelayer.com/v3/").unwrap());
+///
+/// let request = client.get("payments").build().unwrap();
+/// assert_eq!(request.url().as_str(), "https://api.truelayer.com/v3/payments");
+///
+/// let request = client.get("https://truelayer.com").build().unwrap();
+/// assert_eq!(request.url().as_str(), "https://truelayer.com/");
+/// ```
+#[derive(Clone, Debug)]
+pub struct BaseUrlClient {
+    client: ClientWithMiddleware,
+    base_url: Url,
+}
+
+impl BaseUrlClient {
+    /// Create a client sending requests with `client`, relative to `base_url`.
+    pub fn new(client: ClientWithMiddleware, base_url: Url) -> Self {
+        BaseUrlClient { client, base_url }
+    }
+
+    /// Returns the base URL requests are relative to.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Returns a reference to the wrapped [`ClientWithMiddleware`].
+    pub fn client(&self) -> &ClientWithMiddleware {
+        &self.client
+    }
+
+    /// Returns the wrapped [`ClientWithMiddleware`], dropping the base URL.
+    pub fn into_inner(self) -> ClientWithMiddleware {
+        self.client
+    }
+
+    /// Convenience method to make a `GET` request to a path.
+    pub fn get(&self, path: &str) -> RequestBuilder {
+        self.request(Method::GET, path)
+    }
+
+    /// Convenience method to make a `POST` request to a path.
+    pub fn post(&self, path: &str) -> RequestBuilder {
+        self.request(Method::POST, path)
+    }
+
+    /// Convenience method to make a `PUT` request to a path.
+    pub fn put(&self, path: &str) -> RequestBuilder {
+        self.request(Method::PUT, path)
+    }
+
+    /// Convenience method to make a `PATCH` request to a path.
+    pub fn patch(&self, path: &str) -> RequestBuilder {
+        self.request(Method::PATCH, path)
+    }
+
+    /// Convenience method to make a `DELETE` request to a path.
+    pub fn delete(&self, path: &str) -> RequestBuilder {
+        self.request(Method::DELETE, path)
+    }
+
+    /// Convenience method to make a `HEAD` request to a path.
+    pub fn head(&self, path: &str) -> RequestBuilder {
+        self.request(Method::HEAD, path)
+    }
+
+    /// Start building a `Request` with the `Method` and a path resolved against the base URL.
+    ///
+    /// # Errors
+    ///
+    /// Building the request fails if the path can't be resolved against the base URL.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        match self.base_url.join(path) {
+            Ok(url) => self.client.request(method, url),
+            // Let reqwest report the invalid URL when the request is built.
+            Err(_) => self.client.request(method, path),
+        }
+    }
+
+    /// Executes a `Request`, see [`ClientWithMiddleware::execute`].
+    pub async fn execute(&self, req: Request) -> Result<Response> {
+        self.client.execute(req).await
+    }
+
+    /// Executes a `Request` with initial [`Extensions`], see
+    /// [`ClientWithMiddleware::execute_with_extensions`].
+    pub async fn execute_with_extensions(
+        &self,
+        req: Request,
+        ext: &mut Extensions,
+    ) -> Result<Response> {
+        self.client.execute_with_extensions(req, ext).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientBuilder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(base_url: &str) -> BaseUrlClient {
+        ClientBuilder::new(reqwest::Client::new())
+            .build()
+            .with_base_url(Url::parse(base_url).unwrap())
+    }
+
+    #[test]
+    fn resolves_relative_paths_against_the_base_url() {
+        let client = client("https://example.com/api/v1/");
+        for &(path, expected) in &[
+            ("users", "https://example.com/api/v1/users"),
+            (
+                "users/42?expand=true",
+                "https://example.com/api/v1/users/42?expand=true",
+            ),
+            ("../v2/users", "https://example.com/api/v2/users"),
+            ("/health", "https://example.com/health"),
+        ] {
+            let request = client.get(path).build().unwrap();
+            assert_eq!(request.url().as_str(), expected);
+        }
+    }
+
+    #[test]
+    fn uses_absolute_urls_as_is() {
+        let client = client("https://example.com/api/v1/");
+        let request = client
+            .post("https://other.example.com/callback")
+            .build()
+            .unwrap();
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.url().as_str(), "https://other.example.com/callback");
+    }
+
+    #[tokio::test]
+    async fn runs_the_middleware_of_the_wrapped_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/users"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(crate::middleware_fn(move |req, ext, next| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(next.run(req, ext))
+            }))
+            .build()
+            .with_base_url(Url::parse(&format!("{}/api/", server.uri())).unwrap());
+
+        let res = client.get("users").send().await.unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
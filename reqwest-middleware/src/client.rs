@@ -1,17 +1,20 @@
 use http::Extensions;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Body, Client, IntoUrl, Method, Request, Response};
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::convert::TryFrom;
 use std::fmt::{self, Display};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(feature = "multipart")]
 use reqwest::multipart;
 
-use crate::error::Result;
-use crate::middleware::{Middleware, Next};
-use crate::RequestInitialiser;
+use crate::error::{Error, Result};
+use crate::middleware::{Middleware, NamedMiddleware, Next};
+use crate::{DefaultQueryParams, RequestInitialiser};
 
 /// A `ClientBuilder` is used to build a [`ClientWithMiddleware`].
 ///
@@ -20,6 +23,7 @@ pub struct ClientBuilder {
     client: Client,
     middleware_stack: Vec<Arc<dyn Middleware>>,
     initialiser_stack: Vec<Arc<dyn RequestInitialiser>>,
+    default_timeout: Option<Duration>,
 }
 
 impl ClientBuilder {
@@ -28,6 +32,7 @@ impl ClientBuilder {
             client,
             middleware_stack: Vec::new(),
             initialiser_stack: Vec::new(),
+            default_timeout: None,
         }
     }
 
@@ -38,6 +43,7 @@ impl ClientBuilder {
             client: client_with_middleware.inner,
             middleware_stack: client_with_middleware.middleware_stack.into_vec(),
             initialiser_stack: client_with_middleware.initialiser_stack.into_vec(),
+            default_timeout: client_with_middleware.default_timeout,
         }
     }
 
@@ -61,6 +67,36 @@ impl ClientBuilder {
         self
     }
 
+    /// Add middleware to the chain under `name`, which is returned by its [`Middleware::name`].
+    ///
+    /// The middleware can later be looked up with [`ClientWithMiddleware::find_middleware_by_name`].
+    /// Note that it is wrapped in a [`NamedMiddleware`], so [`without_middleware`] has to be called
+    /// with `NamedMiddleware<M>` to remove it.
+    ///
+    /// [`NamedMiddleware`]: crate::NamedMiddleware
+    /// [`without_middleware`]: Self::without_middleware
+    pub fn with_named<M>(self, name: &'static str, middleware: M) -> Self
+    where
+        M: Middleware,
+    {
+        self.with(NamedMiddleware::new(name, middleware))
+    }
+
+    /// Removes all middleware of type `M` from the chain.
+    ///
+    /// Combined with [`from_client`], this allows deriving a client which skips some of the
+    /// middleware of an existing one.
+    ///
+    /// [`from_client`]: Self::from_client
+    pub fn without_middleware<M>(mut self) -> Self
+    where
+        M: Middleware,
+    {
+        self.middleware_stack
+            .retain(|middleware| !(**middleware).as_any().is::<M>());
+        self
+    }
+
     /// Convenience method to attach a request initialiser.
     ///
     /// If you need to keep a reference to the initialiser after attaching, use [`with_arc_init`].
@@ -81,14 +117,150 @@ impl ClientBuilder {
         self
     }
 
+    /// Adds query parameters to every request, e.g. an API key.
+    ///
+    /// `params` is serialized like in [`RequestBuilder::query`], see [`DefaultQueryParams`].
+    pub fn with_default_query_params<T>(self, params: T) -> Self
+    where
+        T: Serialize + Clone + Send + Sync + 'static,
+    {
+        self.with_init(DefaultQueryParams(params))
+    }
+
+    /// Sets a timeout applied to every request that doesn't have one of its own, which can later
+    /// be read with [`ClientWithMiddleware::request_timeout`].
+    ///
+    /// The client passed to [`new`] keeps its configuration: the timeout is set on each request
+    /// right before the middleware stack runs, as with [`RequestBuilder::timeout`], which still
+    /// takes precedence. Each attempt of a request resent by middleware gets the whole timeout,
+    /// use [`ClientWithMiddleware::execute_with_timeout`] to bound the whole execution instead.
+    ///
+    /// [`new`]: Self::new
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Checks the attached middleware for orderings known to be problematic, e.g. tracing
+    /// attached before retries.
+    ///
+    /// Middleware is recognised by its [`Middleware::name`]. This is best effort: an empty result
+    /// doesn't mean the order is right, only that no known anti-pattern was found. Call it before
+    /// [`build`](Self::build), e.g. in a test or a debug assertion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use reqwest::Client;
+    /// use reqwest_middleware::{ClientBuilder, MiddlewareOrderWarning};
+    /// # use reqwest_middleware::{Middleware, Next, Result};
+    /// # struct Passthrough;
+    /// # #[async_trait::async_trait]
+    /// # impl Middleware for Passthrough {
+    /// #     async fn handle(
+    /// #         &self,
+    /// #         req: reqwest::Request,
+    /// #         extensions: &mut http::Extensions,
+    /// #         next: Next<'_>,
+    /// #     ) -> Result<reqwest::Response> {
+    /// #         next.run(req, extensions).await
+    /// #     }
+    /// # }
+    ///
+    /// let builder = ClientBuilder::new(Client::new())
+    ///     .with_named("tracing", Passthrough)
+    ///     .with_named("retry", Passthrough);
+    /// assert_eq!(
+    ///     builder.validate_middleware_order(),
+    ///     vec![MiddlewareOrderWarning::TracingBeforeRetry {
+    ///         tracing: "tracing".to_owned(),
+    ///         retry: "retry".to_owned(),
+    ///     }]
+    /// );
+    /// ```
+    pub fn validate_middleware_order(&self) -> Vec<crate::MiddlewareOrderWarning> {
+        crate::middleware_order::validate(self.middleware_stack.iter().map(|m| m.name()))
+    }
+
     /// Returns a `ClientWithMiddleware` using this builder configuration.
     pub fn build(self) -> ClientWithMiddleware {
         ClientWithMiddleware {
             inner: self.client,
             middleware_stack: self.middleware_stack.into_boxed_slice(),
             initialiser_stack: self.initialiser_stack.into_boxed_slice(),
+            default_timeout: self.default_timeout,
         }
     }
+
+    /// Returns a [`ClientWithMiddleware`] along with a [`MiddlewareSpy`] recording its requests and
+    /// responses, for tests.
+    ///
+    /// The spy is added as the last middleware, so it records requests as they are sent and
+    /// responses before the rest of the middleware stack sees them.
+    ///
+    /// [`MiddlewareSpy`]: crate::MiddlewareSpy
+    #[cfg(feature = "test-utils")]
+    pub fn build_testable(self) -> (ClientWithMiddleware, crate::MiddlewareSpy) {
+        let spy = crate::MiddlewareSpy::default();
+        (self.with(spy.clone()).build(), spy)
+    }
+
+    /// Switch to a statically dispatched, tower-style service chain by adding a [`Layer`].
+    ///
+    /// The result of [`ClientBuilderWithLayer::build`] is the layered [`ReqwestService`] rather than
+    /// a [`ClientWithMiddleware`]. Any [`Middleware`] already attached still runs, inside the
+    /// layers. Request initialisers only apply to [`RequestBuilder`]s, so they are not used by the
+    /// layered service.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use reqwest::Client;
+    /// use reqwest_middleware::ClientBuilder;
+    /// use tower::util::MapRequestLayer;
+    ///
+    /// let service = ClientBuilder::new(Client::new())
+    ///     .with_layer(MapRequestLayer::new(|mut req: reqwest::Request| {
+    ///         req.headers_mut()
+    ///             .insert("x-client", "my-client".parse().unwrap());
+    ///         req
+    ///     }))
+    ///     .build();
+    /// ```
+    ///
+    /// [`Layer`]: tower_layer::Layer
+    /// [`ClientBuilderWithLayer::build`]: crate::ClientBuilderWithLayer::build
+    /// [`ReqwestService`]: crate::ReqwestService
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_layer<L>(self, layer: L) -> crate::ClientBuilderWithLayer<L> {
+        crate::layer::ClientBuilderWithLayer::new(self, layer)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn into_service(self) -> crate::ReqwestService {
+        crate::ReqwestService {
+            client: self.client,
+            middleware_stack: self.middleware_stack.into_boxed_slice(),
+            initialiser_stack: self.initialiser_stack.into_boxed_slice(),
+            default_timeout: self.default_timeout,
+        }
+    }
+
+    /// Returns a `ClientWithMiddleware` wrapped in an [`Arc`], ready to be shared across tasks.
+    ///
+    /// This is equivalent to `Arc::new(builder.build())`. Note that `ClientWithMiddleware` is
+    /// already cheap to clone, so sharing it through an `Arc` is a matter of preference.
+    pub fn build_arc(self) -> Arc<ClientWithMiddleware> {
+        Arc::new(self.build())
+    }
+
+    /// Alias for [`build_arc`].
+    ///
+    /// [`build_arc`]: Self::build_arc
+    pub fn build_and_share(self) -> Arc<ClientWithMiddleware> {
+        self.build_arc()
+    }
 }
 
 /// `ClientWithMiddleware` is a wrapper around [`reqwest::Client`] which runs middleware on every
@@ -98,6 +270,7 @@ pub struct ClientWithMiddleware {
     inner: reqwest::Client,
     middleware_stack: Box<[Arc<dyn Middleware>]>,
     initialiser_stack: Box<[Arc<dyn RequestInitialiser>]>,
+    default_timeout: Option<Duration>,
 }
 
 impl ClientWithMiddleware {
@@ -111,9 +284,114 @@ impl ClientWithMiddleware {
             middleware_stack: middleware_stack.into(),
             // TODO(conradludgate) - allow downstream code to control this manually if desired
             initialiser_stack: Box::new([]),
+            default_timeout: None,
+        }
+    }
+
+    /// Returns the underlying [`reqwest::Client`], dropping all middleware and request
+    /// initialisers.
+    ///
+    /// Requests sent with the returned client bypass the middleware stack entirely, so any
+    /// guarantees it provides (retries, tracing, authentication, ...) are lost.
+    pub fn into_inner(self) -> Client {
+        self.inner
+    }
+
+    /// Returns a reference to the underlying [`reqwest::Client`].
+    ///
+    /// Requests sent with the returned client bypass the middleware stack entirely, see
+    /// [`into_inner`](Self::into_inner).
+    pub fn as_inner(&self) -> &Client {
+        &self.inner
+    }
+
+    /// Returns a clone of the underlying [`reqwest::Client`], without any middleware.
+    ///
+    /// This is cheap, `reqwest::Client` is reference counted. Useful for requests that must not
+    /// be traced, retried or otherwise handled by the middleware stack, e.g. health probes.
+    /// Requests sent with the returned client don't run any middleware or request initialiser.
+    pub fn bare_client(&self) -> Client {
+        self.inner.clone()
+    }
+
+    /// Consuming version of [`bare_client`](Self::bare_client), same as
+    /// [`into_inner`](Self::into_inner).
+    pub fn into_bare_client(self) -> Client {
+        self.inner
+    }
+
+    /// Starts a [`RequestPipeline`], sending a batch of requests concurrently.
+    ///
+    /// This requires the optional `pipeline` feature enabled.
+    ///
+    /// [`RequestPipeline`]: crate::RequestPipeline
+    #[cfg(feature = "pipeline")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pipeline")))]
+    pub fn pipeline(&self) -> crate::RequestPipeline<'_> {
+        crate::RequestPipeline::new(self)
+    }
+
+    /// Returns the default timeout of the client, if it was configured with
+    /// [`ClientBuilder::with_timeout`].
+    ///
+    /// This is `None` when the client was created some other way, even if it has a timeout:
+    /// `reqwest::Client` doesn't expose its configuration.
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.default_timeout
+    }
+
+    /// Returns the middleware stack, in the order it runs.
+    pub fn middlewares(&self) -> &[Arc<dyn Middleware>] {
+        &self.middleware_stack
+    }
+
+    /// Returns an iterator over the middleware stack, in the order it runs.
+    ///
+    /// Iterating over `&ClientWithMiddleware` does the same.
+    pub fn middleware_iter(&self) -> impl Iterator<Item = &Arc<dyn Middleware>> {
+        self.middleware_stack.iter()
+    }
+
+    /// Returns an iterator over the request initialisers, in the order they run.
+    pub fn initialiser_iter(&self) -> impl Iterator<Item = &Arc<dyn RequestInitialiser>> {
+        self.initialiser_stack.iter()
+    }
+
+    /// Returns the first middleware whose [`Middleware::name`] is `name`.
+    ///
+    /// See [`ClientBuilder::with_named`] to attach middleware under a custom name.
+    pub fn find_middleware_by_name(&self, name: &str) -> Option<&Arc<dyn Middleware>> {
+        self.middleware_stack
+            .iter()
+            .find(|middleware| middleware.name() == name)
+    }
+
+    /// Returns a clone of this client with `middleware` appended to its middleware stack.
+    ///
+    /// This client is left untouched, and both clients share all the existing middleware and
+    /// request initialisers.
+    pub fn clone_with_extra_middleware<M>(&self, middleware: M) -> ClientWithMiddleware
+    where
+        M: Middleware,
+    {
+        let mut middleware_stack = self.middleware_stack.to_vec();
+        middleware_stack.push(Arc::new(middleware));
+        ClientWithMiddleware {
+            inner: self.inner.clone(),
+            middleware_stack: middleware_stack.into_boxed_slice(),
+            initialiser_stack: self.initialiser_stack.clone(),
+            default_timeout: self.default_timeout,
         }
     }
 
+    /// Returns a [`BaseUrlClient`] sending requests with this client, to paths relative to
+    /// `base_url`.
+    ///
+    /// [`BaseUrlClient`]: crate::BaseUrlClient
+    pub fn with_base_url(self, base_url: reqwest::Url) -> crate::BaseUrlClient {
+        crate::BaseUrlClient::new(self, base_url)
+    }
+
     /// Convenience method to make a `GET` request to a URL.
     ///
     /// # Errors
@@ -168,6 +446,41 @@ impl ClientWithMiddleware {
         self.request(Method::HEAD, url)
     }
 
+    /// Convenience method to make a `POST` request to a URL with a JSON body.
+    ///
+    /// Shorthand for `self.post(url).json(body)`, see [`RequestBuilder::json`].
+    ///
+    /// This requires the optional `json` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed, or when `body` fails to
+    /// serialize.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn post_json<U: IntoUrl, T: Serialize + ?Sized>(&self, url: U, body: &T) -> RequestBuilder {
+        self.post(url).json(body)
+    }
+
+    /// Convenience method to send a `GET` request to a URL and deserialize the response body as
+    /// JSON.
+    ///
+    /// The status of the response is not checked: a body which isn't valid JSON for `T`, e.g. an
+    /// error page, is reported as a decoding error.
+    ///
+    /// This requires the optional `json` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed, the request fails, or the
+    /// response body is not valid JSON for `T`.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub async fn get_json<U: IntoUrl, T: DeserializeOwned>(&self, url: U) -> Result<T> {
+        let response = self.get(url).send().await?;
+        Ok(response.json::<T>().await?)
+    }
+
     /// Start building a `Request` with the `Method` and `Url`.
     ///
     /// Returns a `RequestBuilder`, which will allow setting headers and
@@ -182,12 +495,81 @@ impl ClientWithMiddleware {
             extensions: Extensions::new(),
             middleware_stack: self.middleware_stack.clone(),
             initialiser_stack: self.initialiser_stack.clone(),
+            default_timeout: self.default_timeout,
         };
         self.initialiser_stack
             .iter()
             .fold(req, |req, i| i.init(req))
     }
 
+    /// Executes a `Request` with a timeout.
+    ///
+    /// The timeout applies to the whole execution, including the middleware stack: the request
+    /// fails once it elapses even if middleware is still resending it, e.g. on retries. The
+    /// timeout configured on the client or the request still applies to each attempt.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of [`execute`], this method fails with an error for which
+    /// [`Error::is_timeout`] returns `true` if the timeout elapses.
+    ///
+    /// [`execute`]: Self::execute
+    pub async fn execute_with_timeout(
+        &self,
+        req: Request,
+        timeout: std::time::Duration,
+    ) -> Result<Response> {
+        let mut ext = Extensions::new();
+        self.execute_with_extensions_timeout(req, &mut ext, timeout)
+            .await
+    }
+
+    /// Executes a `Request` with initial [`Extensions`] and a timeout.
+    ///
+    /// See [`execute_with_timeout`] for how the timeout is applied.
+    ///
+    /// [`execute_with_timeout`]: Self::execute_with_timeout
+    pub async fn execute_with_extensions_timeout(
+        &self,
+        req: Request,
+        ext: &mut Extensions,
+        timeout: std::time::Duration,
+    ) -> Result<Response> {
+        let execution = self.execute_with_extensions(req, ext);
+        #[cfg(not(target_arch = "wasm32"))]
+        let outcome = tokio::time::timeout(timeout, execution).await;
+        #[cfg(target_arch = "wasm32")]
+        let outcome = wasmtimer::tokio::timeout(timeout, execution).await;
+        outcome.unwrap_or_else(|_| Err(Error::middleware(crate::error::TimedOut(timeout))))
+    }
+
+    /// Sends a `HEAD` request to `url`, succeeding only if the response has a `2xx` status.
+    ///
+    /// This is useful to check that dependencies can be reached (and to pre-warm connections)
+    /// at startup, before serving traffic. The request goes through the middleware stack.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the request could not be sent, or if the response status is not
+    /// `2xx`.
+    pub async fn health_check<U: IntoUrl>(&self, url: U) -> Result<()> {
+        let response = self.head(url).send().await?.error_for_status()?;
+        if !response.status().is_success() {
+            // `error_for_status` only covers 4xx and 5xx responses.
+            return Err(Error::middleware_boxed(
+                format!("Health check failed with status {}", response.status()).into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Like [`health_check`], but returns `false` on any error instead of propagating it.
+    ///
+    /// [`health_check`]: Self::health_check
+    pub async fn is_reachable<U: IntoUrl>(&self, url: U) -> bool {
+        self.health_check(url).await.is_ok()
+    }
+
     /// Executes a `Request`.
     ///
     /// A `Request` can be built manually with `Request::new()` or obtained
@@ -205,6 +587,31 @@ impl ClientWithMiddleware {
         self.execute_with_extensions(req, &mut ext).await
     }
 
+    /// Executes a `Request`, returning a stream over the chunks of the response body instead of
+    /// the response.
+    ///
+    /// The middleware stack runs as usual up to the response headers, the body is then read
+    /// lazily as the stream is polled. Middleware that reads the response body (e.g. to log it)
+    /// buffers it anyway.
+    ///
+    /// # Errors
+    ///
+    /// This method fails like [`execute`], and also if the response has a 4xx or 5xx status, as
+    /// with [`Response::error_for_status`]: the error is then returned instead of the body. The
+    /// stream yields an error if reading the body fails.
+    ///
+    /// [`execute`]: Self::execute
+    #[cfg(feature = "stream")]
+    pub async fn execute_stream(
+        &self,
+        req: Request,
+    ) -> Result<impl futures_util::Stream<Item = Result<bytes::Bytes>>> {
+        use futures_util::TryStreamExt;
+
+        let response = self.execute(req).await?.error_for_status()?;
+        Ok(response.bytes_stream().map_err(Error::Reqwest))
+    }
+
     /// Executes a `Request` with initial [`Extensions`].
     ///
     /// A `Request` can be built manually with `Request::new()` or obtained
@@ -213,6 +620,9 @@ impl ClientWithMiddleware {
     /// You should prefer to use the `RequestBuilder` and
     /// `RequestBuilder::send()`.
     ///
+    /// [`RequestInitialiser::on_request`] is invoked for all the request initialisers before
+    /// running the middleware stack.
+    ///
     /// # Errors
     ///
     /// This method fails if there was an error while sending request,
@@ -222,9 +632,35 @@ impl ClientWithMiddleware {
         req: Request,
         ext: &mut Extensions,
     ) -> Result<Response> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let req = self.apply_default_timeout(req);
+        for initialiser in self.initialiser_stack.iter() {
+            initialiser.on_request(&req, ext);
+        }
         let next = Next::new(&self.inner, &self.middleware_stack);
         next.run(req, ext).await
     }
+
+    /// Sets the default timeout on a request that doesn't have one, see
+    /// [`ClientBuilder::with_timeout`].
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_default_timeout(&self, mut req: Request) -> Request {
+        if req.timeout().is_none() {
+            *req.timeout_mut() = self.default_timeout;
+        }
+        req
+    }
+}
+
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let middleware: Vec<&str> = self.middleware_stack.iter().map(|m| m.name()).collect();
+        f.debug_struct("ClientBuilder")
+            .field("client", &self.client)
+            .field("middleware", &middleware)
+            .field("initialisers", &self.initialiser_stack.len())
+            .finish_non_exhaustive()
+    }
 }
 
 /// Create a `ClientWithMiddleware` without any middleware.
@@ -234,10 +670,28 @@ impl From<Client> for ClientWithMiddleware {
             inner: client,
             middleware_stack: Box::new([]),
             initialiser_stack: Box::new([]),
+            default_timeout: None,
         }
     }
 }
 
+/// Extract the underlying [`reqwest::Client`], see [`ClientWithMiddleware::into_inner`].
+impl From<ClientWithMiddleware> for Client {
+    fn from(client: ClientWithMiddleware) -> Self {
+        client.into_inner()
+    }
+}
+
+impl<'a> IntoIterator for &'a ClientWithMiddleware {
+    type Item = &'a Arc<dyn Middleware>;
+    type IntoIter = std::slice::Iter<'a, Arc<dyn Middleware>>;
+
+    /// Iterates over the middleware stack, see [`ClientWithMiddleware::middleware_iter`].
+    fn into_iter(self) -> Self::IntoIter {
+        self.middleware_stack.iter()
+    }
+}
+
 impl fmt::Debug for ClientWithMiddleware {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // skipping middleware_stack field for now
@@ -249,6 +703,8 @@ impl fmt::Debug for ClientWithMiddleware {
 
 #[cfg(not(target_arch = "wasm32"))]
 mod service {
+    #[cfg(feature = "tower-http")]
+    use std::convert::TryFrom;
     use std::{
         future::Future,
         pin::Pin,
@@ -259,7 +715,7 @@ mod service {
     use http::Extensions;
     use reqwest::{Request, Response};
 
-    use crate::{middleware::BoxFuture, ClientWithMiddleware, Next};
+    use crate::{middleware::BoxFuture, ClientWithMiddleware};
 
     // this is meant to be semi-private, same as reqwest's pending
     pub struct Pending {
@@ -276,6 +732,25 @@ mod service {
         }
     }
 
+    #[cfg(feature = "tower-http")]
+    pub struct HttpPending {
+        inner: BoxFuture<'static, Result<http::Response<reqwest::Body>>>,
+    }
+
+    #[cfg(feature = "tower-http")]
+    impl Unpin for HttpPending {}
+
+    #[cfg(feature = "tower-http")]
+    impl Future for HttpPending {
+        type Output = Result<http::Response<reqwest::Body>>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.inner.as_mut().poll(cx)
+        }
+    }
+
+    /// Requests go through the default timeout, the request initialisers and the middleware stack,
+    /// same as with [`ClientWithMiddleware::execute_with_extensions`].
     impl tower_service::Service<Request> for ClientWithMiddleware {
         type Response = Response;
         type Error = crate::Error;
@@ -286,13 +761,75 @@ mod service {
         }
 
         fn call(&mut self, req: Request) -> Self::Future {
-            let inner = self.inner.clone();
-            let middlewares = self.middleware_stack.clone();
+            let client = self.clone();
             let mut extensions = Extensions::new();
             Pending {
                 inner: Box::pin(async move {
-                    let next = Next::new(&inner, &middlewares);
-                    next.run(req, &mut extensions).await
+                    client.execute_with_extensions(req, &mut extensions).await
+                }),
+            }
+        }
+    }
+
+    /// Allows composing `ClientWithMiddleware` with Tower middleware working on [`http::Request`]s
+    /// and [`http::Response`]s, such as the ones provided by `tower-http`.
+    ///
+    /// The request is converted to a [`reqwest::Request`] before running the default timeout, the
+    /// request initialisers and the middleware stack, and its [`http::Extensions`] are handed over
+    /// to them.
+    #[cfg(feature = "tower-http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tower-http")))]
+    impl tower_service::Service<http::Request<bytes::Bytes>> for ClientWithMiddleware {
+        type Response = http::Response<reqwest::Body>;
+        type Error = crate::Error;
+        type Future = HttpPending;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.inner.poll_ready(cx).map_err(crate::Error::Reqwest)
+        }
+
+        fn call(&mut self, req: http::Request<bytes::Bytes>) -> Self::Future {
+            let client = self.clone();
+            let (mut parts, body) = req.into_parts();
+            let mut extensions = std::mem::take(&mut parts.extensions);
+            let req = http::Request::from_parts(parts, body);
+            HttpPending {
+                inner: Box::pin(async move {
+                    let req = Request::try_from(req)?;
+                    client
+                        .execute_with_extensions(req, &mut extensions)
+                        .await
+                        .map(Into::into)
+                }),
+            }
+        }
+    }
+
+    /// Like the [`http::Request<bytes::Bytes>`] service, for requests whose body is already a
+    /// [`reqwest::Body`], e.g. a stream, which is passed through without being buffered.
+    #[cfg(feature = "tower-http")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tower-http")))]
+    impl tower_service::Service<http::Request<reqwest::Body>> for ClientWithMiddleware {
+        type Response = http::Response<reqwest::Body>;
+        type Error = crate::Error;
+        type Future = HttpPending;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+            self.inner.poll_ready(cx).map_err(crate::Error::Reqwest)
+        }
+
+        fn call(&mut self, req: http::Request<reqwest::Body>) -> Self::Future {
+            let client = self.clone();
+            let (mut parts, body) = req.into_parts();
+            let mut extensions = std::mem::take(&mut parts.extensions);
+            let req = http::Request::from_parts(parts, body);
+            HttpPending {
+                inner: Box::pin(async move {
+                    let req = Request::try_from(req)?;
+                    client
+                        .execute_with_extensions(req, &mut extensions)
+                        .await
+                        .map(Into::into)
                 }),
             }
         }
@@ -308,13 +845,11 @@ mod service {
         }
 
         fn call(&mut self, req: Request) -> Self::Future {
-            let inner = self.inner.clone();
-            let middlewares = self.middleware_stack.clone();
+            let client = (*self).clone();
             let mut extensions = Extensions::new();
             Pending {
                 inner: Box::pin(async move {
-                    let next = Next::new(&inner, &middlewares);
-                    next.run(req, &mut extensions).await
+                    client.execute_with_extensions(req, &mut extensions).await
                 }),
             }
         }
@@ -327,6 +862,7 @@ pub struct RequestBuilder {
     inner: reqwest::RequestBuilder,
     middleware_stack: Box<[Arc<dyn Middleware>]>,
     initialiser_stack: Box<[Arc<dyn RequestInitialiser>]>,
+    default_timeout: Option<Duration>,
     extensions: Extensions,
 }
 
@@ -338,6 +874,7 @@ impl RequestBuilder {
             inner,
             middleware_stack: client.middleware_stack,
             initialiser_stack: client.initialiser_stack,
+            default_timeout: client.default_timeout,
             extensions: Extensions::new(),
         }
     }
@@ -550,6 +1087,7 @@ impl RequestBuilder {
             inner,
             middleware_stack,
             initialiser_stack,
+            default_timeout,
             ..
         } = self;
         let (inner, req) = inner.build_split();
@@ -557,10 +1095,21 @@ impl RequestBuilder {
             inner,
             middleware_stack,
             initialiser_stack,
+            default_timeout,
         };
         (client, req)
     }
 
+    /// Build a `Request`, which can be inspected, modified and executed with
+    /// `ClientWithMiddleware::execute_with_extensions()`.
+    ///
+    /// This is similar to [`RequestBuilder::build()`], but also returns the extensions added by
+    /// request initialisers and [`with_extension`](Self::with_extension), e.g. to sign the request
+    /// based on them before executing it.
+    pub fn build_with_extensions(self) -> (reqwest::Result<Request>, Extensions) {
+        (self.inner.build(), self.extensions)
+    }
+
     /// Inserts the extension into this request builder
     pub fn with_extension<T: Send + Sync + Clone + 'static>(mut self, extension: T) -> Self {
         self.extensions.insert(extension);
@@ -572,6 +1121,26 @@ impl RequestBuilder {
         &mut self.extensions
     }
 
+    /// Returns `true` if an extension of type `T` was inserted into this request builder, e.g.
+    /// by a request initialiser.
+    ///
+    /// ```
+    /// use reqwest_middleware::{ClientBuilder, Extension};
+    ///
+    /// #[derive(Clone)]
+    /// struct ApiVersion(u32);
+    ///
+    /// let client = ClientBuilder::new(reqwest::Client::new())
+    ///     .with_init(Extension(ApiVersion(2)))
+    ///     .build();
+    /// let builder = client.get("https://truelayer.com");
+    /// assert!(builder.has_extension::<ApiVersion>());
+    /// assert!(!builder.has_extension::<String>());
+    /// ```
+    pub fn has_extension<T: Send + Sync + 'static>(&self) -> bool {
+        self.extensions.get::<T>().is_some()
+    }
+
     /// Constructs the Request and sends it to the target URL, returning a
     /// future Response.
     ///
@@ -580,6 +1149,10 @@ impl RequestBuilder {
     /// This method fails if there was an error while sending request,
     /// redirect loop was detected or redirect limit was exhausted.
     ///
+    /// A request that can't be built, e.g. because of an invalid URL, fails with an
+    /// [`Error::Reqwest`] for which [`reqwest::Error::is_builder`] is `true`, without running the
+    /// middleware.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -599,11 +1172,35 @@ impl RequestBuilder {
         client.execute_with_extensions(req?, &mut extensions).await
     }
 
+    /// Constructs the Request and sends it with `client` rather than the client the builder was
+    /// created from, e.g. to run a request template against differently configured clients.
+    ///
+    /// The request goes through the middleware, the [`on_request`] hooks of the request
+    /// initialisers and the default timeout of `client`. The [`init`] of its request initialisers
+    /// doesn't run though: the builder was initialised by the client it was created from. The
+    /// extensions already added to the builder are kept.
+    ///
+    /// [`on_request`]: crate::RequestInitialiser::on_request
+    /// [`init`]: crate::RequestInitialiser::init
+    ///
+    /// # Errors
+    ///
+    /// This method fails if there was an error while sending request,
+    /// redirect loop was detected or redirect limit was exhausted.
+    pub async fn execute_on(mut self, client: &ClientWithMiddleware) -> Result<Response> {
+        let mut extensions = std::mem::take(self.extensions());
+        let req = self.build()?;
+        client.execute_with_extensions(req, &mut extensions).await
+    }
+
     /// Attempt to clone the RequestBuilder.
     ///
     /// `None` is returned if the RequestBuilder can not be cloned,
     /// i.e. if the request body is a stream.
     ///
+    /// The extensions added with [`with_extension`](Self::with_extension) are cloned as well, the
+    /// clone and the original can then be modified independently.
+    ///
     /// # Examples
     ///
     /// ```
@@ -623,16 +1220,874 @@ impl RequestBuilder {
             inner,
             middleware_stack: self.middleware_stack.clone(),
             initialiser_stack: self.initialiser_stack.clone(),
+            default_timeout: self.default_timeout,
             extensions: self.extensions.clone(),
         })
     }
 }
 
+/// Builds the `Request`, equivalent to calling [`RequestBuilder::build()`].
+///
+/// The resulting `Request` does not carry the builder's extensions or middleware stack.
+impl TryFrom<RequestBuilder> for Request {
+    type Error = reqwest::Error;
+
+    fn try_from(builder: RequestBuilder) -> std::result::Result<Self, Self::Error> {
+        builder.build()
+    }
+}
+
+/// Builds a `Request` from a clone of the builder, leaving the builder untouched.
+///
+/// This fails if the builder can not be cloned, i.e. if the request body is a stream.
+/// The resulting `Request` does not carry the builder's extensions or middleware stack.
+impl TryFrom<&RequestBuilder> for Request {
+    type Error = Error;
+
+    fn try_from(builder: &RequestBuilder) -> Result<Self> {
+        let inner = builder.inner.try_clone().ok_or_else(|| {
+            Error::middleware_boxed(
+                "Request object is not cloneable. Are you passing a streaming body?".into(),
+            )
+        })?;
+        Ok(inner.build()?)
+    }
+}
+
 impl fmt::Debug for RequestBuilder {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // skipping middleware_stack field for now
         f.debug_struct("RequestBuilder")
             .field("inner", &self.inner)
+            // `Extensions` can't list its content, only count it.
+            .field("extensions", &self.extensions.len())
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn execute_with_timeout_times_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ClientWithMiddleware::from(Client::new());
+        let req = client.get(server.uri()).build().unwrap();
+        let err = client
+            .execute_with_timeout(req, std::time::Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+
+        let req = client.get(server.uri()).build().unwrap();
+        let res = client
+            .execute_with_timeout(req, std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn execute_with_timeout_covers_the_middleware() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        // Each attempt is fast, the middleware resending the request is what takes too long.
+        let client = ClientBuilder::new(Client::new())
+            .with(crate::middleware_fn(|req, extensions, next| {
+                Box::pin(async move {
+                    loop {
+                        let req = req.try_clone().unwrap();
+                        next.clone().run(req, extensions).await?;
+                        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    }
+                })
+            }))
+            .build();
+        let req = client.get(server.uri()).build().unwrap();
+        let err = client
+            .execute_with_timeout(req, std::time::Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        assert!(err.is_middleware());
+    }
+
+    #[tokio::test]
+    async fn default_query_params_are_added_to_every_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/foo"))
+            .and(query_param("api_key", "secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with_default_query_params([("api_key", "secret")])
+            .build();
+        let url = format!("{}/foo", server.uri());
+
+        let req = client.get(&url).build().unwrap();
+        assert_eq!(req.url().query(), Some("api_key=secret"));
+        client.execute(req).await.unwrap();
+
+        let req = client.get(&url).query(&[("page", "2")]).build().unwrap();
+        assert_eq!(req.url().query(), Some("api_key=secret&page=2"));
+        client.execute(req).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn inner_client_bypasses_middleware() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/foo"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with(crate::middleware_fn(|_, _, _| {
+                Box::pin(async { Err(Error::middleware_boxed("middleware was called".into())) })
+            }))
+            .build();
+        let url = format!("{}/foo", server.uri());
+
+        let res = client.as_inner().get(&url).send().await.unwrap();
+        assert_eq!(res.status(), 200);
+
+        let inner: Client = client.into();
+        let res = inner.get(&url).send().await.unwrap();
+        assert_eq!(res.status(), 200);
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn execute_stream_yields_the_body_chunks() {
+        use futures_util::TryStreamExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let body = "a".repeat(100_000);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body.clone()))
+            .mount(&server)
+            .await;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = ClientBuilder::new(Client::new())
+            .with(crate::middleware_fn(move |req, ext, next| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(next.run(req, ext))
+            }))
+            .build();
+
+        let req = client.get(server.uri()).build().unwrap();
+        let chunks: Vec<_> = client
+            .execute_stream(req)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(chunks.concat(), body.as_bytes());
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn execute_stream_fails_on_error_statuses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("error page"))
+            .mount(&server)
+            .await;
+
+        let client = ClientWithMiddleware::from(Client::new());
+        let req = client.get(server.uri()).build().unwrap();
+        let err = match client.execute_stream(req).await {
+            Ok(_) => panic!("the error page was streamed"),
+            Err(err) => err,
+        };
+
+        assert!(err.is_status());
+        assert_eq!(
+            err.status(),
+            Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn post_json_sends_the_serialized_body() {
+        use wiremock::matchers::body_string;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("content-type", "application/json"))
+            .and(body_string(r#"["a","b"]"#))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new()).build();
+        let res = client
+            .post_json(server.uri(), &["a", "b"])
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 201);
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn get_json_deserializes_the_body() {
+        use std::collections::HashMap;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id":"abc"}"#))
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new()).build();
+        let body: HashMap<String, String> = client.get_json(server.uri()).await.unwrap();
+        assert_eq!(body["id"], "abc");
+
+        let err = client
+            .get_json::<_, Vec<u32>>(server.uri())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Reqwest(ref e) if e.is_decode()),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_on_uses_the_given_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("x-client", "other"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client_with_header = |name: &'static str| {
+            ClientBuilder::new(Client::new())
+                .with_init(move |req: RequestBuilder| req.header("x-init", name))
+                .with(crate::middleware_fn(move |mut req, ext, next| {
+                    req.headers_mut()
+                        .insert("x-client", HeaderValue::from_static(name));
+                    Box::pin(next.run(req, ext))
+                }))
+                .build()
+        };
+        let template = client_with_header("original").get(server.uri());
+
+        let res = template
+            .execute_on(&client_with_header("other"))
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+        // Only the initialisers of the client the builder was created from ran.
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests[0].headers["x-init"], "original");
+    }
+
+    #[tokio::test]
+    async fn invalid_urls_are_builder_errors() {
+        let client = ClientBuilder::new(Client::new())
+            .with(crate::middleware_fn(|_req, _ext, _next| {
+                panic!("the middleware must not run for an invalid request")
+            }))
+            .build();
+
+        let err = client.get("not a url").send().await.unwrap_err();
+        assert!(
+            matches!(err, Error::Reqwest(ref e) if e.is_builder()),
+            "{:?}",
+            err
+        );
+
+        let err = client
+            .get("http://[::1")
+            .execute_on(&client)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Reqwest(ref e) if e.is_builder()),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn bare_client_bypasses_middleware() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/foo"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with(crate::middleware_fn(|_, _, _| {
+                Box::pin(async { Err(Error::middleware_boxed("middleware was called".into())) })
+            }))
+            .build();
+        let url = format!("{}/foo", server.uri());
+
+        let res = client.bare_client().get(&url).send().await.unwrap();
+        assert_eq!(res.status(), 200);
+        assert!(client.get(&url).send().await.is_err());
+
+        let res = client.into_bare_client().get(&url).send().await.unwrap();
+        assert_eq!(res.status(), 200);
+    }
+
+    #[cfg(feature = "tower-http")]
+    #[tokio::test]
+    async fn composes_with_tower_http_layers() {
+        use tower::{Service, ServiceBuilder, ServiceExt};
+        use tower_http::set_header::SetRequestHeaderLayer;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/foo"))
+            .and(wiremock::matchers::header("x-foo", "bar"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new()).build();
+        let mut service = ServiceBuilder::new()
+            .layer(SetRequestHeaderLayer::overriding(
+                HeaderName::from_static("x-foo"),
+                HeaderValue::from_static("bar"),
+            ))
+            .service(client);
+
+        let req = http::Request::get(format!("{}/foo", server.uri()))
+            .body(bytes::Bytes::new())
+            .unwrap();
+        let res = ServiceExt::<http::Request<bytes::Bytes>>::ready(&mut service)
+            .await
+            .unwrap()
+            .call(req)
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+
+    #[cfg(feature = "tower-http")]
+    #[tokio::test]
+    async fn serves_http_requests_with_a_reqwest_body() {
+        use tower::{Service, ServiceExt};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/foo"))
+            .and(header("x-foo", "bar"))
+            .and(wiremock::matchers::body_string("request body"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .insert_header("x-baz", "qux")
+                    .set_body_string("response body"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut client = ClientBuilder::new(Client::new()).build();
+        let req = http::Request::post(format!("{}/foo", server.uri()))
+            .header("x-foo", "bar")
+            .body(reqwest::Body::from("request body"))
+            .unwrap();
+        let res = ServiceExt::<http::Request<reqwest::Body>>::ready(&mut client)
+            .await
+            .unwrap()
+            .call(req)
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.headers()["x-baz"], "qux");
+        let body = Response::from(res).text().await.unwrap();
+        assert_eq!(body, "response body");
+    }
+
+    #[test]
+    fn build_with_extensions_returns_accumulated_extensions() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct FromInit(&'static str);
+        #[derive(Clone, Debug, PartialEq)]
+        struct FromBuilder(u32);
+
+        let client = ClientBuilder::new(Client::new())
+            .with_init(crate::Extension(FromInit("init")))
+            .build();
+        let (req, extensions) = client
+            .get("https://example.com/foo")
+            .with_extension(FromBuilder(42))
+            .build_with_extensions();
+
+        assert_eq!(req.unwrap().url().path(), "/foo");
+        assert_eq!(extensions.get::<FromInit>(), Some(&FromInit("init")));
+        assert_eq!(extensions.get::<FromBuilder>(), Some(&FromBuilder(42)));
+    }
+
+    #[tokio::test]
+    async fn health_check_succeeds_on_2xx() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = ClientWithMiddleware::from(Client::new());
+        let url = format!("{}/health", server.uri());
+        client.health_check(&url).await.unwrap();
+        assert!(client.is_reachable(&url).await);
+    }
+
+    #[tokio::test]
+    async fn health_check_fails_on_error_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = ClientWithMiddleware::from(Client::new());
+        let url = format!("{}/health", server.uri());
+        let err = client.health_check(&url).await.unwrap_err();
+        assert_eq!(err.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!client.is_reachable(&url).await);
+    }
+
+    #[test]
+    fn request_try_from_builder() {
+        let client = ClientWithMiddleware::from(Client::new());
+        let builder = client
+            .get("http://example.com/foo")
+            .header("x-foo", "bar")
+            .with_extension(42u32);
+
+        let from_ref = Request::try_from(&builder).unwrap();
+        assert_eq!(from_ref.url().as_str(), "http://example.com/foo");
+        assert_eq!(from_ref.headers()["x-foo"], "bar");
+
+        let from_owned = Request::try_from(builder).unwrap();
+        assert_eq!(from_owned.url().as_str(), "http://example.com/foo");
+        assert_eq!(from_owned.headers()["x-foo"], "bar");
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Visited(Vec<&'static str>);
+
+    struct VisitA;
+    struct VisitB;
+
+    fn visit(ext: &mut Extensions, name: &'static str) {
+        match ext.get_mut::<Visited>() {
+            Some(Visited(visited)) => visited.push(name),
+            None => {
+                ext.insert(Visited(vec![name]));
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Middleware for VisitA {
+        async fn handle(
+            &self,
+            req: Request,
+            extensions: &mut Extensions,
+            next: Next<'_>,
+        ) -> Result<Response> {
+            visit(extensions, "a");
+            next.run(req, extensions).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Middleware for VisitB {
+        async fn handle(
+            &self,
+            req: Request,
+            extensions: &mut Extensions,
+            next: Next<'_>,
+        ) -> Result<Response> {
+            visit(extensions, "b");
+            next.run(req, extensions).await
+        }
+    }
+
+    #[tokio::test]
+    async fn without_middleware_removes_middleware_by_type() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with(VisitA)
+            .with(VisitB)
+            .with(VisitA)
+            .build();
+        let without_a = ClientBuilder::from_client(client.clone())
+            .without_middleware::<VisitA>()
+            .build();
+
+        for (client, expected) in [(client, vec!["a", "b", "a"]), (without_a, vec!["b"])] {
+            let req = client.get(server.uri()).build().unwrap();
+            let mut extensions = Extensions::new();
+            client
+                .execute_with_extensions(req, &mut extensions)
+                .await
+                .unwrap();
+            assert_eq!(extensions.get::<Visited>(), Some(&Visited(expected)));
+        }
+    }
+
+    #[tokio::test]
+    async fn clone_with_extra_middleware_leaves_the_original_untouched() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new()).with(VisitA).build();
+        let extended = client.clone_with_extra_middleware(VisitB);
+
+        for (client, expected) in [(client, vec!["a"]), (extended, vec!["a", "b"])] {
+            let req = client.get(server.uri()).build().unwrap();
+            let mut extensions = Extensions::new();
+            client
+                .execute_with_extensions(req, &mut extensions)
+                .await
+                .unwrap();
+            assert_eq!(extensions.get::<Visited>(), Some(&Visited(expected)));
+        }
+    }
+
+    #[test]
+    fn as_any_exposes_the_concrete_middleware_type() {
+        let middleware: Arc<dyn Middleware> = Arc::new(VisitA);
+        assert!((*middleware).as_any().is::<VisitA>());
+        assert!(!(*middleware).as_any().is::<VisitB>());
+    }
+
+    #[test]
+    fn named_middleware_can_be_found() {
+        let client = ClientBuilder::new(Client::new())
+            .with(VisitA)
+            .with_named("visit-b", VisitB)
+            .build();
+
+        let middleware = client.find_middleware_by_name("visit-b").unwrap();
+        assert_eq!(middleware.name(), "visit-b");
+        assert!((**middleware)
+            .as_any()
+            .is::<crate::NamedMiddleware<VisitB>>());
+        assert!(client.find_middleware_by_name("visit-a").is_none());
+    }
+
+    #[test]
+    fn middleware_name_defaults_to_the_type_name() {
+        let client = ClientBuilder::new(Client::new()).with(VisitA).build();
+
+        let names: Vec<_> = client.middlewares().iter().map(|m| m.name()).collect();
+        assert_eq!(names, [std::any::type_name::<VisitA>()]);
+    }
+
+    #[test]
+    fn iterators_follow_the_configured_order() {
+        let first_init: Arc<dyn RequestInitialiser> = Arc::new(crate::Extension(1u8));
+        let second_init: Arc<dyn RequestInitialiser> = Arc::new(crate::Extension(2u16));
+        let client = ClientBuilder::new(Client::new())
+            .with(VisitA)
+            .with_arc_init(first_init.clone())
+            .with(VisitB)
+            .with_arc_init(second_init.clone())
+            .build();
+
+        let names: Vec<_> = client.middleware_iter().map(|m| m.name()).collect();
+        assert_eq!(
+            names,
+            [
+                std::any::type_name::<VisitA>(),
+                std::any::type_name::<VisitB>()
+            ]
+        );
+        let names: Vec<_> = (&client).into_iter().map(|m| m.name()).collect();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0], std::any::type_name::<VisitA>());
+
+        let initialisers: Vec<_> = client.initialiser_iter().collect();
+        assert_eq!(initialisers.len(), 2);
+        assert!(Arc::ptr_eq(initialisers[0], &first_init));
+        assert!(Arc::ptr_eq(initialisers[1], &second_init));
+    }
+
+    #[test]
+    fn request_timeout_returns_the_configured_timeout() {
+        let timeout = Duration::from_secs(5);
+        let client = ClientBuilder::new(Client::new())
+            .with_timeout(timeout)
+            .build();
+        assert_eq!(client.request_timeout(), Some(timeout));
+
+        let (client, _) = client.get("http://localhost").build_split();
+        assert_eq!(client.request_timeout(), Some(timeout));
+    }
+
+    #[tokio::test]
+    async fn default_timeout_applies_to_requests_without_one() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with_timeout(Duration::from_millis(50))
+            .build();
+        assert_eq!(client.request_timeout(), Some(Duration::from_millis(50)));
+
+        let err = client.get(server.uri()).send().await.unwrap_err();
+        assert!(err.is_timeout());
+
+        let res = client
+            .get(server.uri())
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn service_applies_the_default_timeout_and_initialisers() {
+        use tower::ServiceExt;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let seen = Arc::new(std::sync::Mutex::new(None));
+        let recorder = seen.clone();
+        let client = ClientBuilder::new(Client::new())
+            .with_timeout(Duration::from_secs(5))
+            .with_init(RecordPath)
+            .with(crate::middleware_fn(move |req, ext, next| {
+                *recorder.lock().unwrap() = Some((req.timeout().copied(), ext.get().cloned()));
+                Box::pin(next.run(req, ext))
+            }))
+            .build();
+
+        let req = Request::new(
+            Method::GET,
+            format!("{}/accounts/123", server.uri()).parse().unwrap(),
+        );
+        let res = client.oneshot(req).await.unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            Some((
+                Some(Duration::from_secs(5)),
+                Some(RequestPath("/accounts/123".to_owned()))
+            ))
+        );
+    }
+
+    #[test]
+    fn request_timeout_is_unknown_by_default() {
+        let client = ClientBuilder::new(Client::new()).build();
+        assert_eq!(client.request_timeout(), None);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct RequestPath(String);
+
+    struct RecordPath;
+
+    impl RequestInitialiser for RecordPath {
+        fn init(&self, req: RequestBuilder) -> RequestBuilder {
+            req
+        }
+
+        fn on_request(&self, req: &Request, ext: &mut Extensions) {
+            ext.insert(RequestPath(req.url().path().to_owned()));
+        }
+    }
+
+    #[tokio::test]
+    async fn initialisers_see_the_built_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with_init(RecordPath)
+            .build();
+
+        let req = Request::new(
+            Method::GET,
+            format!("{}/accounts/123", server.uri()).parse().unwrap(),
+        );
+        let mut extensions = Extensions::new();
+        client
+            .execute_with_extensions(req, &mut extensions)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            extensions.get::<RequestPath>(),
+            Some(&RequestPath("/accounts/123".to_owned()))
+        );
+    }
+
+    #[test]
+    fn try_clone_clones_the_extensions() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Marker(&'static str);
+
+        let client = ClientWithMiddleware::from(Client::new());
+        let builder = client
+            .get("http://localhost")
+            .with_extension(Marker("original"));
+
+        let mut clone = builder.try_clone().unwrap();
+        assert_eq!(clone.extensions().get(), Some(&Marker("original")));
+
+        clone.extensions().insert(Marker("clone"));
+        let (_, extensions) = builder.build_with_extensions();
+        assert_eq!(extensions.get(), Some(&Marker("original")));
+    }
+
+    #[test]
+    fn validate_middleware_order_detects_tracing_before_retry() {
+        let builder = ClientBuilder::new(Client::new())
+            .with_named("reqwest_tracing::TracingMiddleware<MyBackend>", VisitA)
+            .with_named("reqwest_retry::RetryTransientMiddleware", VisitB);
+
+        assert_eq!(
+            builder.validate_middleware_order(),
+            vec![crate::MiddlewareOrderWarning::TracingBeforeRetry {
+                tracing: "reqwest_tracing::TracingMiddleware<MyBackend>".to_owned(),
+                retry: "reqwest_retry::RetryTransientMiddleware".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn validate_middleware_order_detects_retry_before_rate_limiter() {
+        let builder = ClientBuilder::new(Client::new())
+            .with_named("retry", VisitA)
+            .with_named("my_app::RateLimiter", VisitB);
+
+        assert_eq!(
+            builder.validate_middleware_order(),
+            vec![crate::MiddlewareOrderWarning::RetryBeforeRateLimiter {
+                retry: "retry".to_owned(),
+                rate_limiter: "my_app::RateLimiter".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn validate_middleware_order_accepts_recommended_order() {
+        let builder = ClientBuilder::new(Client::new())
+            .with_named("rate_limiter", VisitA)
+            .with_named("retry", VisitB)
+            // Generic parameters are not taken into account.
+            .with_named("TracingMiddleware<RetryAwareBackend>", VisitA)
+            .with(VisitB);
+
+        assert_eq!(builder.validate_middleware_order(), vec![]);
+    }
+
+    #[test]
+    fn validate_middleware_order_ignores_module_paths() {
+        let builder = ClientBuilder::new(Client::new())
+            .with_named("my_app::tracing::AuthMiddleware", VisitA)
+            .with_named("my_app::retry::HeadersMiddleware", VisitB)
+            .with_named("my_app::rate_limit::LogMiddleware", VisitA);
+
+        assert_eq!(builder.validate_middleware_order(), vec![]);
+    }
+
+    #[test]
+    fn client_builder_debug_lists_middleware_names() {
+        let builder = ClientBuilder::new(Client::new())
+            .with_named("visit-a", VisitA)
+            .with_named("visit-b", VisitB)
+            .with_init(crate::Extension(Visited(vec![])));
+
+        let debug = format!("{:?}", builder);
+        assert!(
+            debug.starts_with("ClientBuilder { client: Client {"),
+            "{}",
+            debug
+        );
+        assert!(
+            debug.ends_with(r#"middleware: ["visit-a", "visit-b"], initialisers: 1, .. }"#),
+            "{}",
+            debug
+        );
+    }
+
+    #[test]
+    fn request_builder_debug_counts_extensions() {
+        let client = ClientBuilder::new(Client::new())
+            .with_init(crate::Extension(Visited(vec![])))
+            .build();
+        let builder = client.get("http://localhost").with_extension(42u32);
+
+        assert!(builder.has_extension::<Visited>());
+        assert!(builder.has_extension::<u32>());
+        assert!(!builder.has_extension::<String>());
+        let debug = format!("{:?}", builder);
+        assert!(debug.ends_with("extensions: 2, .. }"), "{}", debug);
+    }
+}
@@ -3,22 +3,179 @@ use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// A type-erased error, as returned by middleware in [`Error::Middleware`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
 #[derive(Error, Debug)]
 pub enum Error {
     /// There was an error running some middleware
     #[error(transparent)]
-    Middleware(#[from] anyhow::Error),
+    Middleware(BoxError),
     /// Error from the underlying reqwest client
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 }
 
+/// An error returned by middleware, with hints on how to handle it.
+///
+/// Create the [`Error`](enum@Error) with [`Error::from_middleware_error`] to keep the hints, which are then
+/// available with [`Error::is_retryable_middleware_error`] and [`Error::http_status_hint`], e.g.
+/// for `reqwest-retry` to retry the request.
+///
+/// ```
+/// use reqwest_middleware::{Error, MiddlewareError};
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("rate limited by the token endpoint")]
+/// struct TokenRateLimited;
+///
+/// impl MiddlewareError for TokenRateLimited {
+///     fn is_retryable(&self) -> bool {
+///         true
+///     }
+/// }
+///
+/// let err = Error::from_middleware_error(TokenRateLimited);
+/// assert!(err.is_retryable_middleware_error());
+/// assert!(err.downcast_middleware_error::<TokenRateLimited>().is_some());
+/// ```
+pub trait MiddlewareError: std::error::Error + Send + Sync + 'static {
+    /// Whether sending the request again may succeed. `false` by default.
+    fn is_retryable(&self) -> bool {
+        false
+    }
+
+    /// The HTTP status the error corresponds to, if any. `None` by default.
+    fn http_status_hint(&self) -> Option<StatusCode> {
+        None
+    }
+}
+
+/// A [`MiddlewareError`] stored in an [`Error::Middleware`], along with its hints.
+///
+/// It is transparent, displaying and returning the sources of the wrapped error.
+#[derive(Debug)]
+struct TypedMiddlewareError {
+    inner: BoxError,
+    is_retryable: bool,
+    http_status_hint: Option<StatusCode>,
+}
+
+impl std::fmt::Display for TypedMiddlewareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl std::error::Error for TypedMiddlewareError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+/// The error of [`ClientWithMiddleware::execute_with_timeout`] when the timeout elapses.
+///
+/// [`ClientWithMiddleware::execute_with_timeout`]: crate::ClientWithMiddleware::execute_with_timeout
+#[derive(Debug, Error)]
+#[error("request timed out after {0:?}")]
+pub(crate) struct TimedOut(pub(crate) std::time::Duration);
+
+/// An [`anyhow::Error`] converted into an [`Error::Middleware`], kept as is so that
+/// [`Error::downcast_middleware_error`] can look through its context.
+struct AnyhowError(anyhow::Error);
+
+impl std::fmt::Debug for AnyhowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::fmt::Display for AnyhowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for AnyhowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Kept for backwards compatibility with middleware returning [`anyhow::Error`]s.
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        Error::Middleware(Box::new(AnyhowError(err)))
+    }
+}
+
 impl Error {
+    /// Creates an [`Error::Middleware`] from any error type.
     pub fn middleware<E>(err: E) -> Self
     where
         E: 'static + Send + Sync + std::error::Error,
     {
-        Error::Middleware(err.into())
+        Error::Middleware(Box::new(err))
+    }
+
+    /// Creates an [`Error::Middleware`] from an already boxed error.
+    ///
+    /// Strings can be converted into a [`BoxError`] too, e.g.
+    /// `Error::middleware_boxed("something went wrong".into())`.
+    pub fn middleware_boxed(err: BoxError) -> Self {
+        Error::Middleware(err)
+    }
+
+    /// Creates an [`Error::Middleware`] from a [`MiddlewareError`], keeping its hints.
+    pub fn from_middleware_error<E: MiddlewareError>(err: E) -> Self {
+        Error::Middleware(Box::new(TypedMiddlewareError {
+            is_retryable: err.is_retryable(),
+            http_status_hint: err.http_status_hint(),
+            inner: Box::new(err),
+        }))
+    }
+
+    /// Returns the middleware error of type `E`, whether it was created with
+    /// [`from_middleware_error`](Self::from_middleware_error), [`middleware`](Self::middleware)
+    /// or converted from an [`anyhow::Error`], even with added context.
+    ///
+    /// Prefer it to downcasting the [`BoxError`] of [`Error::Middleware`] directly, which holds a
+    /// wrapper in the first and last cases.
+    pub fn downcast_middleware_error<E: MiddlewareError>(&self) -> Option<&E> {
+        let err = match self {
+            Error::Middleware(err) => err,
+            Error::Reqwest(_) => return None,
+        };
+        if let Some(typed) = err.downcast_ref::<TypedMiddlewareError>() {
+            typed.inner.downcast_ref()
+        } else if let Some(AnyhowError(err)) = err.downcast_ref() {
+            err.downcast_ref()
+        } else {
+            err.downcast_ref()
+        }
+    }
+
+    /// Returns [`MiddlewareError::is_retryable`] for errors created with
+    /// [`from_middleware_error`](Self::from_middleware_error), `false` for any other error.
+    pub fn is_retryable_middleware_error(&self) -> bool {
+        self.typed_middleware_error()
+            .is_some_and(|typed| typed.is_retryable)
+    }
+
+    /// Returns [`MiddlewareError::http_status_hint`] for errors created with
+    /// [`from_middleware_error`](Self::from_middleware_error), `None` for any other error.
+    ///
+    /// Unlike [`status`](Self::status), it is never set for `reqwest` errors.
+    pub fn http_status_hint(&self) -> Option<StatusCode> {
+        self.typed_middleware_error()
+            .and_then(|typed| typed.http_status_hint)
+    }
+
+    fn typed_middleware_error(&self) -> Option<&TypedMiddlewareError> {
+        match self {
+            Error::Middleware(err) => err.downcast_ref(),
+            Error::Reqwest(_) => None,
+        }
     }
 
     /// Returns a possible URL related to this error.
@@ -93,7 +250,7 @@ impl Error {
     /// Returns true if the error is related to a timeout.
     pub fn is_timeout(&self) -> bool {
         match self {
-            Error::Middleware(_) => false,
+            Error::Middleware(e) => e.is::<TimedOut>(),
             Error::Reqwest(e) => e.is_timeout(),
         }
     }
@@ -138,4 +295,259 @@ impl Error {
             Error::Reqwest(e) => e.status(),
         }
     }
+
+    /// Returns the message of this error followed by the messages of its sources, see
+    /// [`cause_chain`].
+    pub fn cause_chain(&self) -> Vec<String> {
+        cause_chain(self)
+    }
+}
+
+/// Returns the message of `err` followed by the messages of all its sources, outermost first.
+///
+/// For an [`Error::Middleware`] created from an [`anyhow::Error`], the sources are the contexts
+/// and the root cause of the `anyhow` chain.
+///
+/// ```
+/// use anyhow::Context;
+/// use reqwest_middleware::Error;
+///
+/// let err: anyhow::Result<()> = Err(anyhow::anyhow!("connection refused"));
+/// let err: Error = err.context("fetching the token").unwrap_err().into();
+/// assert_eq!(err.cause_chain(), ["fetching the token", "connection refused"]);
+/// ```
+pub fn cause_chain(err: &(dyn std::error::Error + 'static)) -> Vec<String> {
+    std::iter::successors(Some(err), |err| err.source())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Converts an [`Error`](enum@Error) into a gRPC [`tonic::Status`], e.g. to return it from a tonic service.
+///
+/// - Timeouts, including the one of [`execute_with_timeout`], are mapped to
+///   [`Code::DeadlineExceeded`](tonic::Code::DeadlineExceeded)
+/// - `4xx` statuses are mapped to [`Code::InvalidArgument`](tonic::Code::InvalidArgument)
+/// - `5xx` statuses are mapped to [`Code::Unavailable`](tonic::Code::Unavailable)
+/// - Other middleware errors are mapped to [`Code::Internal`](tonic::Code::Internal)
+/// - Any other error is mapped to [`Code::Unknown`](tonic::Code::Unknown)
+///
+/// This conversion is lossy: only the error message is kept. Implement your own conversion if
+/// you need a different mapping or more details.
+///
+/// [`execute_with_timeout`]: crate::ClientWithMiddleware::execute_with_timeout
+#[cfg(feature = "tonic")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tonic")))]
+impl From<Error> for tonic::Status {
+    fn from(err: Error) -> Self {
+        let message = err.to_string();
+        // Checked first, as the timeout of `execute_with_timeout` is a middleware error.
+        if err.is_timeout() {
+            return tonic::Status::deadline_exceeded(message);
+        }
+        if err.is_middleware() {
+            return tonic::Status::internal(message);
+        }
+        match err.status() {
+            Some(status) if status.is_client_error() => tonic::Status::invalid_argument(message),
+            Some(status) if status.is_server_error() => tonic::Status::unavailable(message),
+            _ => tonic::Status::unknown(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn middleware_errors_expose_the_anyhow_chain() {
+        let err = anyhow::anyhow!("root cause")
+            .context("middle")
+            .context("outer");
+        let err = Error::from(err);
+
+        assert_eq!(err.to_string(), "outer");
+        let source = err.source().unwrap();
+        assert_eq!(source.to_string(), "middle");
+        assert_eq!(source.source().unwrap().to_string(), "root cause");
+        assert_eq!(err.cause_chain(), ["outer", "middle", "root cause"]);
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("token endpoint unavailable")]
+    struct TokenUnavailable(#[source] std::io::Error);
+
+    impl MiddlewareError for TokenUnavailable {
+        fn is_retryable(&self) -> bool {
+            true
+        }
+
+        fn http_status_hint(&self) -> Option<StatusCode> {
+            Some(StatusCode::SERVICE_UNAVAILABLE)
+        }
+    }
+
+    #[test]
+    fn typed_middleware_errors_keep_their_hints() {
+        let err = Error::from_middleware_error(TokenUnavailable(std::io::Error::other("refused")));
+
+        assert!(err.is_middleware());
+        assert!(err.is_retryable_middleware_error());
+        assert_eq!(
+            err.http_status_hint(),
+            Some(StatusCode::SERVICE_UNAVAILABLE)
+        );
+        assert!(err
+            .downcast_middleware_error::<TokenUnavailable>()
+            .is_some());
+        assert_eq!(err.cause_chain(), ["token endpoint unavailable", "refused"]);
+    }
+
+    #[test]
+    fn middleware_errors_are_inspected_with_downcast_middleware_error() {
+        let typed = Error::from_middleware_error(TokenUnavailable(std::io::Error::other("a")));
+        let Error::Middleware(boxed) = &typed else {
+            unreachable!("a middleware error")
+        };
+        // The boxed error is the private wrapper holding the hints.
+        assert!(boxed.downcast_ref::<TokenUnavailable>().is_none());
+        assert!(typed
+            .downcast_middleware_error::<TokenUnavailable>()
+            .is_some());
+
+        let from_anyhow = Error::from(anyhow::Error::new(TokenUnavailable(std::io::Error::other(
+            "b",
+        ))));
+        let Error::Middleware(boxed) = &from_anyhow else {
+            unreachable!("a middleware error")
+        };
+        assert!(boxed.downcast_ref::<TokenUnavailable>().is_none());
+        assert!(from_anyhow
+            .downcast_middleware_error::<TokenUnavailable>()
+            .is_some());
+
+        let with_context = Error::from(
+            anyhow::Error::new(TokenUnavailable(std::io::Error::other("c"))).context("fetching"),
+        );
+        assert!(with_context
+            .downcast_middleware_error::<TokenUnavailable>()
+            .is_some());
+        assert_eq!(
+            with_context.cause_chain(),
+            ["fetching", "token endpoint unavailable", "c"]
+        );
+    }
+
+    #[test]
+    fn untyped_middleware_errors_have_no_hints() {
+        let err = Error::middleware(TokenUnavailable(std::io::Error::other("refused")));
+
+        assert!(!err.is_retryable_middleware_error());
+        assert_eq!(err.http_status_hint(), None);
+        assert!(err
+            .downcast_middleware_error::<TokenUnavailable>()
+            .is_some());
+    }
+
+    #[test]
+    fn reqwest_errors_expose_their_chain() {
+        let err: Error = reqwest::Client::new()
+            .get("not a url")
+            .build()
+            .unwrap_err()
+            .into();
+
+        let chain = err.cause_chain();
+        assert_eq!(chain[0], err.to_string());
+        assert_eq!(chain.len(), 2, "{:?}", chain);
+        assert_eq!(chain[1], err.source().unwrap().to_string());
+    }
+
+    #[cfg(feature = "tonic")]
+    mod tonic_status {
+        use super::*;
+
+        use tonic::Code;
+        use wiremock::matchers::any;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        fn status_error(status: u16) -> Error {
+            let response = http::Response::builder().status(status).body("").unwrap();
+            reqwest::Response::from(response)
+                .error_for_status()
+                .unwrap_err()
+                .into()
+        }
+
+        #[test]
+        fn status_errors_are_converted_to_grpc_status() {
+            assert_eq!(
+                tonic::Status::from(status_error(404)).code(),
+                Code::InvalidArgument
+            );
+            assert_eq!(
+                tonic::Status::from(status_error(503)).code(),
+                Code::Unavailable
+            );
+        }
+
+        #[test]
+        fn middleware_errors_are_internal() {
+            let err = Error::middleware_boxed("middleware failure".into());
+            let status = tonic::Status::from(err);
+            assert_eq!(status.code(), Code::Internal);
+            assert_eq!(status.message(), "middleware failure");
+        }
+
+        #[tokio::test]
+        async fn timeouts_exceed_the_deadline() {
+            let server = MockServer::start().await;
+            Mock::given(any())
+                .respond_with(
+                    ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(500)),
+                )
+                .mount(&server)
+                .await;
+
+            let err: Error = reqwest::Client::new()
+                .get(server.uri())
+                .timeout(std::time::Duration::from_millis(50))
+                .send()
+                .await
+                .unwrap_err()
+                .into();
+            assert_eq!(tonic::Status::from(err).code(), Code::DeadlineExceeded);
+        }
+
+        #[tokio::test]
+        async fn execution_timeouts_exceed_the_deadline() {
+            let server = MockServer::start().await;
+            Mock::given(any())
+                .respond_with(
+                    ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(500)),
+                )
+                .mount(&server)
+                .await;
+
+            let client = crate::ClientBuilder::new(reqwest::Client::new()).build();
+            let req = client.get(server.uri()).build().unwrap();
+            let err = client
+                .execute_with_timeout(req, std::time::Duration::from_millis(50))
+                .await
+                .unwrap_err();
+            assert!(err.is_middleware());
+            assert_eq!(tonic::Status::from(err).code(), Code::DeadlineExceeded);
+        }
+
+        #[test]
+        fn other_errors_are_unknown() {
+            let err: Error = reqwest::Client::new()
+                .get("not a url")
+                .build()
+                .unwrap_err()
+                .into();
+            assert_eq!(tonic::Status::from(err).code(), Code::Unknown);
+        }
+    }
 }
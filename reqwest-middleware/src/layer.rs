@@ -0,0 +1,289 @@
+//! Statically dispatched, tower-style composition.
+//!
+//! [`Middleware`] is stored as `Arc<dyn Middleware>` and every call goes through a vtable and a
+//! boxed future. For hot paths where the middleware is known at compile time, a [`Layer`] stack
+//! built with [`ClientBuilder::with_layer`] wraps a [`ReqwestService`] directly, so the whole
+//! chain is a single concrete type that the compiler can inline.
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use http::Extensions;
+use reqwest::{Client, Request, Response};
+use tower_layer::{Layer, Stack};
+use tower_service::Service;
+
+use crate::middleware::BoxFuture;
+use crate::{ClientBuilder, Middleware, Next, RequestInitialiser, Result};
+
+/// The innermost [`Service`] of a layered client.
+///
+/// It sends requests with the wrapped [`reqwest::Client`]. If the [`ClientBuilder`] it was created
+/// from had any [`Middleware`] or [`RequestInitialiser`] attached, they run inside the layers, right
+/// before the request is sent, and so does its default timeout. Without dynamic middleware and
+/// request initialisers no allocation or dynamic dispatch takes place.
+#[derive(Clone)]
+pub struct ReqwestService {
+    pub(crate) client: Client,
+    pub(crate) middleware_stack: Box<[Arc<dyn Middleware>]>,
+    pub(crate) initialiser_stack: Box<[Arc<dyn RequestInitialiser>]>,
+    pub(crate) default_timeout: Option<Duration>,
+}
+
+impl ReqwestService {
+    /// Create a service sending requests with `client`, without any dynamic middleware.
+    pub fn new(client: Client) -> Self {
+        ReqwestService {
+            client,
+            middleware_stack: Box::new([]),
+            initialiser_stack: Box::new([]),
+            default_timeout: None,
+        }
+    }
+}
+
+impl From<Client> for ReqwestService {
+    fn from(client: Client) -> Self {
+        Self::new(client)
+    }
+}
+
+impl std::fmt::Debug for ReqwestService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReqwestService")
+            .field("client", &self.client)
+            .field("middleware_stack", &self.middleware_stack.len())
+            .field("initialiser_stack", &self.initialiser_stack.len())
+            .field("default_timeout", &self.default_timeout)
+            .finish()
+    }
+}
+
+impl Service<Request> for ReqwestService {
+    type Response = Response;
+    type Error = crate::Error;
+    type Future = ReqwestServiceFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.client.poll_ready(cx).map_err(crate::Error::Reqwest)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        if req.timeout().is_none() {
+            *req.timeout_mut() = self.default_timeout;
+        }
+        if self.middleware_stack.is_empty() && self.initialiser_stack.is_empty() {
+            return ReqwestServiceFuture::Direct(self.client.call(req));
+        }
+
+        let mut extensions = Extensions::new();
+        for initialiser in self.initialiser_stack.iter() {
+            initialiser.on_request(&req, &mut extensions);
+        }
+        let client = self.client.clone();
+        let middlewares = self.middleware_stack.clone();
+        ReqwestServiceFuture::Middleware(Box::pin(async move {
+            let next = Next::new(&client, &middlewares);
+            next.run(req, &mut extensions).await
+        }))
+    }
+}
+
+/// Future returned by [`ReqwestService`].
+pub enum ReqwestServiceFuture {
+    #[doc(hidden)]
+    Direct(<Client as Service<Request>>::Future),
+    #[doc(hidden)]
+    Middleware(BoxFuture<'static, Result<Response>>),
+}
+
+impl Future for ReqwestServiceFuture {
+    type Output = Result<Response>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut() {
+            ReqwestServiceFuture::Direct(fut) => {
+                Pin::new(fut).poll(cx).map_err(crate::Error::Reqwest)
+            }
+            ReqwestServiceFuture::Middleware(fut) => fut.as_mut().poll(cx),
+        }
+    }
+}
+
+/// A [`ClientBuilder`] with a statically typed stack of [`Layer`]s.
+///
+/// Created by [`ClientBuilder::with_layer`]. Layers run in the order they were added, the first
+/// one being the outermost, same as [`ClientBuilder::with`].
+pub struct ClientBuilderWithLayer<L> {
+    builder: ClientBuilder,
+    layer: L,
+}
+
+impl<L> ClientBuilderWithLayer<L> {
+    pub(crate) fn new(builder: ClientBuilder, layer: L) -> Self {
+        ClientBuilderWithLayer { builder, layer }
+    }
+
+    /// Add another layer, which will run after (inside) the ones already added.
+    pub fn with_layer<T>(self, layer: T) -> ClientBuilderWithLayer<Stack<T, L>> {
+        ClientBuilderWithLayer {
+            builder: self.builder,
+            layer: Stack::new(layer, self.layer),
+        }
+    }
+
+    /// Returns the service obtained by wrapping a [`ReqwestService`] with all the layers.
+    pub fn build(self) -> L::Service
+    where
+        L: Layer<ReqwestService>,
+    {
+        self.layer.layer(self.builder.into_service())
+    }
+}
+
+impl<L> std::fmt::Debug for ClientBuilderWithLayer<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilderWithLayer")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Clone)]
+    struct CountLayer(Arc<AtomicUsize>);
+
+    impl<S> Layer<S> for CountLayer {
+        type Service = Count<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            Count(self.0.clone(), inner)
+        }
+    }
+
+    #[derive(Clone)]
+    struct Count<S>(Arc<AtomicUsize>, S);
+
+    impl<S: Service<Request>> Service<Request> for Count<S> {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), S::Error>> {
+            self.1.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: Request) -> Self::Future {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            self.1.call(req)
+        }
+    }
+
+    #[tokio::test]
+    async fn layers_wrap_the_reqwest_service() {
+        use tower::ServiceExt;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let outer = Arc::new(AtomicUsize::new(0));
+        let inner = Arc::new(AtomicUsize::new(0));
+        let service = ClientBuilder::new(Client::new())
+            .with_layer(CountLayer(outer.clone()))
+            .with_layer(CountLayer(inner.clone()))
+            .build();
+
+        let req = Request::new(reqwest::Method::GET, server.uri().parse().unwrap());
+        let res = service.oneshot(req).await.unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(outer.load(Ordering::SeqCst), 1);
+        assert_eq!(inner.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn dynamic_middleware_runs_inside_the_layers() {
+        use tower::ServiceExt;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let service = ClientBuilder::new(Client::new())
+            .with(crate::middleware_fn(move |req, ext, next| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Box::pin(next.run(req, ext))
+            }))
+            .with_layer(tower_layer::layer_fn(|s| s))
+            .build();
+
+        let req = Request::new(reqwest::Method::GET, server.uri().parse().unwrap());
+        let res = service.oneshot(req).await.unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn default_timeout_and_initialisers_run_inside_the_layers() {
+        use tower::ServiceExt;
+
+        #[derive(Clone)]
+        struct Initialised;
+
+        struct MarkInitialised;
+
+        impl RequestInitialiser for MarkInitialised {
+            fn init(&self, req: crate::RequestBuilder) -> crate::RequestBuilder {
+                req
+            }
+
+            fn on_request(&self, _req: &Request, ext: &mut Extensions) {
+                ext.insert(Initialised);
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .mount(&server)
+            .await;
+
+        let initialised = Arc::new(AtomicUsize::new(0));
+        let counter = initialised.clone();
+        let service = ClientBuilder::new(Client::new())
+            .with_timeout(Duration::from_millis(50))
+            .with_init(MarkInitialised)
+            .with(crate::middleware_fn(move |req, ext, next| {
+                if ext.get::<Initialised>().is_some() {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+                Box::pin(next.run(req, ext))
+            }))
+            .with_layer(tower_layer::layer_fn(|s| s))
+            .build();
+
+        let req = Request::new(reqwest::Method::GET, server.uri().parse().unwrap());
+        let err = service.oneshot(req).await.unwrap_err();
+
+        assert!(err.is_timeout());
+        assert_eq!(initialised.load(Ordering::SeqCst), 1);
+    }
+}
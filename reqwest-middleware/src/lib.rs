@@ -49,13 +49,37 @@
 #[cfg(doctest)]
 pub struct ReadmeDoctests;
 
+mod base_url;
 mod client;
 mod error;
+#[cfg(not(target_arch = "wasm32"))]
+mod layer;
 mod middleware;
+mod middleware_order;
+#[cfg(feature = "oauth2")]
+pub mod oauth2;
+#[cfg(feature = "pipeline")]
+mod pipeline;
 mod req_init;
+mod resend_count;
+#[cfg(feature = "test-utils")]
+mod spy;
 
+pub use base_url::BaseUrlClient;
 pub use client::{ClientBuilder, ClientWithMiddleware, RequestBuilder};
-pub use error::{Error, Result};
-pub use middleware::{Middleware, Next};
-pub use req_init::{Extension, RequestInitialiser};
+pub use error::{cause_chain, BoxError, Error, MiddlewareError, Result};
+#[cfg(not(target_arch = "wasm32"))]
+pub use layer::{ClientBuilderWithLayer, ReqwestService, ReqwestServiceFuture};
+pub use middleware::{
+    middleware_fn, AsAny, ClosureMiddleware, FnMiddleware, Middleware, NamedMiddleware, Next,
+};
+pub use middleware_order::MiddlewareOrderWarning;
+#[cfg(feature = "pipeline")]
+pub use pipeline::RequestPipeline;
+pub use req_init::{DefaultQueryParams, Extension, RequestInitialiser, RequestStack};
 pub use reqwest;
+pub use resend_count::ResendCount;
+#[cfg(feature = "test-utils")]
+pub use spy::MiddlewareSpy;
+pub use tower_layer::Layer;
+pub use tower_service::Service;
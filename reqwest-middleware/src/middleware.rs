@@ -3,6 +3,7 @@ use reqwest::{Client, Request, Response};
 
 use crate::error::{Error, Result};
 
+use std::any::Any;
 use std::sync::Arc;
 
 /// When attached to a [`ClientWithMiddleware`] (generally using [`with`]), middleware is run
@@ -34,7 +35,7 @@ use std::sync::Arc;
 /// [`with`]: crate::ClientBuilder::with
 #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
-pub trait Middleware: 'static + Send + Sync {
+pub trait Middleware: AsAny + 'static + Send + Sync {
     /// Invoked with a request before sending it. If you want to continue processing the request,
     /// you should explicitly call `next.run(req, extensions)`.
     ///
@@ -46,6 +47,74 @@ pub trait Middleware: 'static + Send + Sync {
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> Result<Response>;
+
+    /// A human readable name for this middleware, e.g. for debugging or runtime inspection.
+    ///
+    /// Defaults to the name of the implementing type. Use [`ClientBuilder::with_named`] to
+    /// attach middleware under a custom name.
+    ///
+    /// [`ClientBuilder::with_named`]: crate::ClientBuilder::with_named
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// A [`Middleware`] attached under a custom name, created with [`ClientBuilder::with_named`].
+///
+/// It delegates to the wrapped middleware, except for [`Middleware::name`].
+///
+/// [`ClientBuilder::with_named`]: crate::ClientBuilder::with_named
+pub struct NamedMiddleware<M> {
+    name: &'static str,
+    inner: M,
+}
+
+impl<M> NamedMiddleware<M> {
+    /// Wrap `inner`, reporting `name` as its [`Middleware::name`].
+    pub fn new(name: &'static str, inner: M) -> Self {
+        NamedMiddleware { name, inner }
+    }
+
+    /// Returns a reference to the wrapped middleware.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<M: Middleware> Middleware for NamedMiddleware<M> {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        self.inner.handle(req, extensions, next).await
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+/// Upcasts a [`Middleware`] to [`Any`], so that its concrete type can be inspected.
+///
+/// This is implemented for every middleware, e.g. to let [`ClientBuilder::without_middleware`]
+/// find the middleware to remove.
+///
+/// Note that `Arc<dyn Middleware>` implements `AsAny` itself, so call it on the dereferenced
+/// middleware, e.g. `(*middleware).as_any()`.
+///
+/// [`ClientBuilder::without_middleware`]: crate::ClientBuilder::without_middleware
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
@@ -67,6 +136,69 @@ where
     }
 }
 
+/// Creates a [`Middleware`] from a closure.
+///
+/// Any `Fn(Request, &mut Extensions, Next<'_>) -> BoxFuture<'_, Result<Response>>` closure is
+/// already a [`Middleware`], but the compiler struggles to infer the closure signature when it is
+/// passed straight to [`with`]. Wrapping the closure with `middleware_fn` spells the signature out.
+///
+/// # Example
+///
+/// ```
+/// use reqwest::{header::HeaderValue, Client};
+/// use reqwest_middleware::{middleware_fn, ClientBuilder};
+///
+/// let client = ClientBuilder::new(Client::new())
+///     .with(middleware_fn(|mut req, extensions, next| {
+///         Box::pin(async move {
+///             req.headers_mut()
+///                 .insert("x-client", HeaderValue::from_static("my-client"));
+///             let res = next.run(req, extensions).await;
+///             if let Ok(res) = &res {
+///                 println!("Response status: {}", res.status());
+///             }
+///             res
+///         })
+///     }))
+///     .build();
+/// ```
+///
+/// [`with`]: crate::ClientBuilder::with
+pub fn middleware_fn<F>(f: F) -> ClosureMiddleware<F>
+where
+    F: Send
+        + Sync
+        + 'static
+        + for<'a> Fn(Request, &'a mut Extensions, Next<'a>) -> BoxFuture<'a, Result<Response>>,
+{
+    ClosureMiddleware(f)
+}
+
+/// A [`Middleware`] backed by a closure, created with [`middleware_fn`].
+pub struct ClosureMiddleware<F>(F);
+
+/// Alias for [`ClosureMiddleware`].
+pub type FnMiddleware<F> = ClosureMiddleware<F>;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<F> Middleware for ClosureMiddleware<F>
+where
+    F: Send
+        + Sync
+        + 'static
+        + for<'a> Fn(Request, &'a mut Extensions, Next<'a>) -> BoxFuture<'a, Result<Response>>,
+{
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        (self.0)(req, extensions, next).await
+    }
+}
+
 /// Next encapsulates the remaining middleware chain to run in [`Middleware::handle`]. You can
 /// forward the request down the chain with [`run`].
 ///
@@ -91,6 +223,16 @@ impl<'a> Next<'a> {
         }
     }
 
+    /// Returns the number of middleware left to run before the request is sent.
+    pub fn remaining_middleware_count(&self) -> usize {
+        self.middlewares.len()
+    }
+
+    /// Returns `true` if there is more middleware to run before the request is sent.
+    pub fn has_more_middleware(&self) -> bool {
+        !self.middlewares.is_empty()
+    }
+
     pub fn run(
         mut self,
         req: Request,
@@ -104,3 +246,107 @@ impl<'a> Next<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ClientBuilder;
+    use reqwest::StatusCode;
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // Middleware futures are spawned on multi-threaded runtimes, which requires them to be `Send`.
+    #[cfg(not(target_arch = "wasm32"))]
+    static_assertions::assert_impl_all!(Next<'static>: Send, Sync);
+    #[cfg(not(target_arch = "wasm32"))]
+    static_assertions::assert_impl_all!(BoxFuture<'static, Result<Response>>: Send);
+
+    #[derive(Clone)]
+    struct ObservedStatus(StatusCode);
+
+    #[derive(Clone, Default)]
+    struct RemainingCounts(Vec<(usize, bool)>);
+
+    struct RecordRemaining;
+
+    #[async_trait::async_trait]
+    impl Middleware for RecordRemaining {
+        async fn handle(
+            &self,
+            req: Request,
+            extensions: &mut Extensions,
+            next: Next<'_>,
+        ) -> Result<Response> {
+            extensions
+                .get_or_insert_default::<RemainingCounts>()
+                .0
+                .push((
+                    next.remaining_middleware_count(),
+                    next.has_more_middleware(),
+                ));
+            next.run(req, extensions).await
+        }
+    }
+
+    #[tokio::test]
+    async fn next_reports_remaining_middleware() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with(RecordRemaining)
+            .with(RecordRemaining)
+            .with(RecordRemaining)
+            .build();
+
+        let req = client.get(server.uri()).build().unwrap();
+        let mut extensions = Extensions::new();
+        client
+            .execute_with_extensions(req, &mut extensions)
+            .await
+            .unwrap();
+
+        let RemainingCounts(counts) = extensions.get().cloned().unwrap();
+        assert_eq!(counts, [(2, true), (1, true), (0, false)]);
+    }
+
+    #[tokio::test]
+    async fn middleware_fn_mutates_extensions_and_inspects_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("x-foo", "bar"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(Client::new())
+            .with(middleware_fn(|mut req, extensions, next| {
+                Box::pin(async move {
+                    req.headers_mut()
+                        .insert("x-foo", http::HeaderValue::from_static("bar"));
+                    let res = next.run(req, extensions).await;
+                    if let Ok(res) = &res {
+                        extensions.insert(ObservedStatus(res.status()));
+                    }
+                    res
+                })
+            }))
+            .build();
+
+        let req = client.get(server.uri()).build().unwrap();
+        let mut extensions = Extensions::new();
+        let res = client
+            .execute_with_extensions(req, &mut extensions)
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let ObservedStatus(observed) = extensions.get().cloned().unwrap();
+        assert_eq!(observed, StatusCode::CREATED);
+    }
+}
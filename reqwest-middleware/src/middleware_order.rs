@@ -0,0 +1,91 @@
+use std::fmt;
+
+/// A problematic ordering of middleware, found by [`ClientBuilder::validate_middleware_order`].
+///
+/// Middleware is recognised by its [`Middleware::name`], so custom names given with
+/// [`ClientBuilder::with_named`] should keep the usual type name in them (e.g. `"retry"` or
+/// `"tracing"`) for the validation to be meaningful.
+///
+/// [`ClientBuilder::validate_middleware_order`]: crate::ClientBuilder::validate_middleware_order
+/// [`ClientBuilder::with_named`]: crate::ClientBuilder::with_named
+/// [`Middleware::name`]: crate::Middleware::name
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MiddlewareOrderWarning {
+    /// A tracing middleware is attached before a retry middleware, so all the attempts of a
+    /// request are recorded in a single span and the resend count is never reported. The retry
+    /// middleware should be attached first.
+    TracingBeforeRetry { tracing: String, retry: String },
+    /// A retry middleware is attached before a rate limiter, so every retry counts toward the
+    /// limit. The rate limiter should be attached first.
+    RetryBeforeRateLimiter { retry: String, rate_limiter: String },
+}
+
+impl fmt::Display for MiddlewareOrderWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MiddlewareOrderWarning::TracingBeforeRetry { tracing, retry } => write!(
+                f,
+                "`{}` is attached before `{}`, retries won't get their own span",
+                tracing, retry
+            ),
+            MiddlewareOrderWarning::RetryBeforeRateLimiter {
+                retry,
+                rate_limiter,
+            } => write!(
+                f,
+                "`{}` is attached before `{}`, retries count toward the rate limit",
+                retry, rate_limiter
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Tracing,
+    Retry,
+    RateLimiter,
+}
+
+/// Guess what a middleware does from its name, ignoring any generic parameters and the path of
+/// its type, e.g. `my_app::tracing::AuthMiddleware` is neither a tracing nor a retry middleware.
+fn kind(name: &str) -> Option<Kind> {
+    let name = name.split('<').next().unwrap_or(name);
+    let name = name.rsplit("::").next().unwrap_or(name).to_lowercase();
+    if name.contains("retry") {
+        Some(Kind::Retry)
+    } else if name.contains("tracing") {
+        Some(Kind::Tracing)
+    } else if name.contains("ratelimit") || name.contains("rate_limit") {
+        Some(Kind::RateLimiter)
+    } else {
+        None
+    }
+}
+
+/// Checks the middleware names, in the order they were attached, for known anti-patterns.
+pub(crate) fn validate<'a>(names: impl Iterator<Item = &'a str>) -> Vec<MiddlewareOrderWarning> {
+    let names: Vec<_> = names.map(|name| (name, kind(name))).collect();
+    let mut warnings = Vec::new();
+    for (i, &(first, first_kind)) in names.iter().enumerate() {
+        for &(second, second_kind) in &names[i + 1..] {
+            match (first_kind, second_kind) {
+                (Some(Kind::Tracing), Some(Kind::Retry)) => {
+                    warnings.push(MiddlewareOrderWarning::TracingBeforeRetry {
+                        tracing: first.to_owned(),
+                        retry: second.to_owned(),
+                    })
+                }
+                (Some(Kind::Retry), Some(Kind::RateLimiter)) => {
+                    warnings.push(MiddlewareOrderWarning::RetryBeforeRateLimiter {
+                        retry: first.to_owned(),
+                        rate_limiter: second.to_owned(),
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    warnings
+}
@@ -0,0 +1,235 @@
+//! Bearer token authentication for OAuth2 access tokens.
+use std::sync::Arc;
+
+use http::header::{HeaderValue, AUTHORIZATION};
+use http::Extensions;
+use reqwest::{Request, Response, StatusCode};
+
+use crate::{Error, Middleware, Next, Result};
+
+/// Source of OAuth2 access tokens for [`OAuth2Middleware`].
+///
+/// Implementations are expected to cache the token and refresh it before it expires.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait TokenCache: 'static + Send + Sync {
+    /// Returns a valid access token, fetching a new one if needed.
+    async fn token(&self) -> Result<String>;
+
+    /// Drops the cached token, so that the next call to [`token`] fetches a new one.
+    ///
+    /// Called by [`OAuth2Middleware`] when the server rejects a token with
+    /// `401 Unauthorized`, see [`OAuth2Middleware::with_token_refresh_on_401`].
+    ///
+    /// [`token`]: Self::token
+    fn invalidate(&self);
+}
+
+/// Middleware adding an `Authorization: Bearer <token>` header to every request, with the token
+/// taken from a [`TokenCache`].
+///
+/// This is a [`Middleware`] rather than a [`RequestInitialiser`] since fetching the token is
+/// asynchronous.
+///
+/// # Example
+///
+/// ```
+/// use reqwest_middleware::oauth2::{OAuth2Middleware, TokenCache};
+/// use reqwest_middleware::{ClientBuilder, Result};
+///
+/// struct MyTokens;
+///
+/// #[async_trait::async_trait]
+/// impl TokenCache for MyTokens {
+///     async fn token(&self) -> Result<String> {
+///         Ok("my-access-token".to_owned())
+///     }
+///
+///     fn invalidate(&self) {}
+/// }
+///
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with(OAuth2Middleware::new(MyTokens).with_token_refresh_on_401(true))
+///     .build();
+/// ```
+///
+/// [`RequestInitialiser`]: crate::RequestInitialiser
+#[derive(Clone)]
+pub struct OAuth2Middleware {
+    token_cache: Arc<dyn TokenCache>,
+    token_refresh_on_401: bool,
+}
+
+impl OAuth2Middleware {
+    /// Create the middleware from a [`TokenCache`] owned by the middleware.
+    pub fn new<T: TokenCache>(token_cache: T) -> Self {
+        Self::from_arc(Arc::new(token_cache))
+    }
+
+    /// Create the middleware from a [`TokenCache`] shared with the rest of the application.
+    pub fn from_arc(token_cache: Arc<dyn TokenCache>) -> Self {
+        OAuth2Middleware {
+            token_cache,
+            token_refresh_on_401: false,
+        }
+    }
+
+    /// When enabled, a request answered with `401 Unauthorized` invalidates the cached token and
+    /// is sent once more with a new one. Requests whose body can't be cloned are never resent.
+    ///
+    /// Disabled by default.
+    pub fn with_token_refresh_on_401(mut self, token_refresh_on_401: bool) -> Self {
+        self.token_refresh_on_401 = token_refresh_on_401;
+        self
+    }
+
+    async fn authorize(&self, req: &mut Request) -> Result<()> {
+        let token = self.token_cache.token().await?;
+        let mut value =
+            HeaderValue::from_str(&format!("Bearer {}", token)).map_err(Error::middleware)?;
+        value.set_sensitive(true);
+        req.headers_mut().insert(AUTHORIZATION, value);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for OAuth2Middleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth2Middleware")
+            .field("token_refresh_on_401", &self.token_refresh_on_401)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for OAuth2Middleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let retry = if self.token_refresh_on_401 {
+            req.try_clone()
+        } else {
+            None
+        };
+
+        self.authorize(&mut req).await?;
+        let res = next.clone().run(req, extensions).await?;
+
+        match retry {
+            Some(mut retry) if res.status() == StatusCode::UNAUTHORIZED => {
+                self.token_cache.invalidate();
+                self.authorize(&mut retry).await?;
+                next.run(retry, extensions).await
+            }
+            _ => Ok(res),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientBuilder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Hands out `token-0`, then `token-1` after the first invalidation and so on.
+    #[derive(Default)]
+    struct CountingTokens {
+        generation: AtomicUsize,
+        invalidations: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl TokenCache for CountingTokens {
+        async fn token(&self) -> Result<String> {
+            Ok(format!("token-{}", self.generation.load(Ordering::SeqCst)))
+        }
+
+        fn invalidate(&self) {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            self.invalidations.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    async fn server_accepting(token: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header(
+                "authorization",
+                format!("Bearer {}", token).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn injects_the_token() {
+        let server = server_accepting("token-0").await;
+        let tokens = Arc::new(CountingTokens::default());
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(OAuth2Middleware::from_arc(tokens.clone()))
+            .build();
+
+        let res = client.get(server.uri()).send().await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(tokens.invalidations.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn refreshes_the_token_on_401() {
+        let server = server_accepting("token-1").await;
+        let tokens = Arc::new(CountingTokens::default());
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(OAuth2Middleware::from_arc(tokens.clone()).with_token_refresh_on_401(true))
+            .build();
+
+        let res = client.get(server.uri()).send().await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(tokens.invalidations.load(Ordering::SeqCst), 1);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn retries_only_once_on_401() {
+        let server = server_accepting("never-valid").await;
+        let tokens = Arc::new(CountingTokens::default());
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(OAuth2Middleware::from_arc(tokens.clone()).with_token_refresh_on_401(true))
+            .build();
+
+        let res = client.get(server.uri()).send().await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(tokens.invalidations.load(Ordering::SeqCst), 1);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_refresh_unless_enabled() {
+        let server = server_accepting("token-1").await;
+        let tokens = Arc::new(CountingTokens::default());
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(OAuth2Middleware::from_arc(tokens.clone()))
+            .build();
+
+        let res = client.get(server.uri()).send().await.unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(tokens.invalidations.load(Ordering::SeqCst), 0);
+    }
+}
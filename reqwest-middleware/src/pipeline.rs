@@ -0,0 +1,117 @@
+//! Sending a batch of requests concurrently, see [`RequestPipeline`].
+use reqwest::Response;
+
+use crate::{ClientWithMiddleware, RequestBuilder, Result};
+
+/// A batch of requests sent concurrently with [`send`](Self::send), created with
+/// [`ClientWithMiddleware::pipeline`].
+///
+/// The requests are all dispatched at once, without waiting for the previous responses. Whether
+/// they share a network round-trip depends on the connection: over HTTP/2 they are multiplexed on
+/// a single connection, when both `reqwest` and the server support it. Over HTTP/1.1 `reqwest`
+/// doesn't pipeline requests on a connection, they are sent on separate pooled connections
+/// instead, opening new ones if needed.
+///
+/// Every request runs through the middleware stack and the `on_request` hooks of the request
+/// initialisers of the client the pipeline was created from, see [`RequestBuilder::execute_on`].
+/// The requests are initialised by the clients their builders were created from.
+///
+/// This requires the optional `pipeline` feature enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() {
+/// let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+///
+/// let mut pipeline = client.pipeline();
+/// pipeline
+///     .add(client.get("https://truelayer.com/a"))
+///     .add(client.get("https://truelayer.com/b"));
+/// // One result per request, in the order they were added.
+/// let responses = pipeline.send().await;
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "a pipeline does nothing until it is sent"]
+pub struct RequestPipeline<'a> {
+    client: &'a ClientWithMiddleware,
+    requests: Vec<RequestBuilder>,
+}
+
+impl<'a> RequestPipeline<'a> {
+    pub(crate) fn new(client: &'a ClientWithMiddleware) -> Self {
+        RequestPipeline {
+            client,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Adds a request to the pipeline.
+    pub fn add(&mut self, request: RequestBuilder) -> &mut Self {
+        self.requests.push(request);
+        self
+    }
+
+    /// Returns the number of requests in the pipeline.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns `true` if no request was added to the pipeline.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Sends all the requests concurrently and waits for all of them to complete.
+    ///
+    /// The results are in the order the requests were added, a failed request doesn't prevent the
+    /// others from completing.
+    pub async fn send(self) -> Vec<Result<Response>> {
+        let client = self.client;
+        futures_util::future::join_all(
+            self.requests
+                .into_iter()
+                .map(|request| request.execute_on(client)),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ClientBuilder;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn responses_are_in_the_order_of_the_requests() {
+        let server = MockServer::start().await;
+        for &(route, delay) in &[("/slow", 200), ("/fast", 0)] {
+            Mock::given(method("GET"))
+                .and(path(route))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_string(route)
+                        .set_delay(std::time::Duration::from_millis(delay)),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let client = ClientBuilder::new(reqwest::Client::new()).build();
+        let mut pipeline = client.pipeline();
+        pipeline
+            .add(client.get(format!("{}/slow", server.uri())))
+            .add(client.get("not a url"))
+            .add(client.get(format!("{}/fast", server.uri())));
+        assert_eq!(pipeline.len(), 3);
+
+        let mut results = pipeline.send().await.into_iter();
+        let slow = results.next().unwrap().unwrap();
+        assert_eq!(slow.text().await.unwrap(), "/slow");
+        assert!(results.next().unwrap().unwrap_err().is_builder());
+        let fast = results.next().unwrap().unwrap();
+        assert_eq!(fast.text().await.unwrap(), "/fast");
+    }
+}
@@ -1,3 +1,8 @@
+use http::Extensions;
+use reqwest::Request;
+use serde::Serialize;
+use tower_layer::Layer;
+
 use crate::RequestBuilder;
 
 /// When attached to a [`ClientWithMiddleware`] (generally using [`with_init`]), it is run
@@ -21,6 +26,20 @@ use crate::RequestBuilder;
 /// [`with_init`]: crate::ClientBuilder::with_init
 pub trait RequestInitialiser: 'static + Send + Sync {
     fn init(&self, req: RequestBuilder) -> RequestBuilder;
+
+    /// Invoked with the built request, right before it is handed to the middleware stack by
+    /// [`ClientWithMiddleware::execute_with_extensions`], the `Service` implementations of
+    /// `ClientWithMiddleware` and [`ReqwestService`].
+    ///
+    /// This gives read-only access to the request as configured, e.g. to pick an auth token
+    /// based on the URL, and can be used to set extensions for the middleware. Unlike
+    /// [`init`](Self::init), it is also invoked for requests built without a [`RequestBuilder`].
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// [`ClientWithMiddleware::execute_with_extensions`]: crate::ClientWithMiddleware::execute_with_extensions
+    /// [`ReqwestService`]: crate::ReqwestService
+    fn on_request(&self, _req: &Request, _ext: &mut Extensions) {}
 }
 
 impl<F> RequestInitialiser for F
@@ -32,6 +51,74 @@ where
     }
 }
 
+/// Two [`RequestInitialiser`]s combined into one, in the style of tower's `Stack`.
+///
+/// `outer` runs first, then `inner`, both in [`init`] and in [`on_request`]. Attaching a
+/// `RequestStack` with [`with_init`] is the same as attaching `outer` then `inner`, but the
+/// combination is a single statically typed initialiser, which can be built and passed around
+/// on its own.
+///
+/// A `RequestStack` of two [`Layer`]s is a [`Layer`] as well, wrapping services the same way as
+/// tower's `Stack`, with `outer` as the outermost layer.
+///
+/// ```
+/// use reqwest_middleware::{ClientBuilder, Extension, RequestBuilder, RequestStack};
+///
+/// #[derive(Clone)]
+/// struct ClientName(&'static str);
+///
+/// let defaults = RequestStack::new(
+///     |req: RequestBuilder| req.bearer_auth("my_auth_token"),
+///     Extension(ClientName("my-client")),
+/// );
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with_init(defaults)
+///     .build();
+/// ```
+///
+/// [`init`]: RequestInitialiser::init
+/// [`on_request`]: RequestInitialiser::on_request
+/// [`with_init`]: crate::ClientBuilder::with_init
+#[derive(Clone, Debug)]
+pub struct RequestStack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<Inner, Outer> RequestStack<Inner, Outer> {
+    /// Combine `inner` and `outer`, `outer` running first.
+    pub fn new(inner: Inner, outer: Outer) -> Self {
+        RequestStack { inner, outer }
+    }
+}
+
+impl<Inner, Outer> RequestInitialiser for RequestStack<Inner, Outer>
+where
+    Inner: RequestInitialiser,
+    Outer: RequestInitialiser,
+{
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        self.inner.init(self.outer.init(req))
+    }
+
+    fn on_request(&self, req: &Request, ext: &mut Extensions) {
+        self.outer.on_request(req, ext);
+        self.inner.on_request(req, ext);
+    }
+}
+
+impl<S, Inner, Outer> Layer<S> for RequestStack<Inner, Outer>
+where
+    Inner: Layer<S>,
+    Outer: Layer<Inner::Service>,
+{
+    type Service = Outer::Service;
+
+    fn layer(&self, service: S) -> Self::Service {
+        self.outer.layer(self.inner.layer(service))
+    }
+}
+
 /// A middleware that inserts the value into the [`Extensions`](http::Extensions) during the call.
 ///
 /// This is a good way to inject extensions to middleware deeper in the stack
@@ -82,3 +169,82 @@ impl<T: Send + Sync + Clone + 'static> RequestInitialiser for Extension<T> {
         req.with_extension(self.0.clone())
     }
 }
+
+/// A request initialiser that adds query parameters to every request.
+///
+/// Generally attached using [`with_default_query_params`]. The parameters are added when the
+/// request is created, so calling [`query`] on the request builder appends to them rather than
+/// replacing them.
+///
+/// [`with_default_query_params`]: crate::ClientBuilder::with_default_query_params
+/// [`query`]: crate::RequestBuilder::query
+pub struct DefaultQueryParams<T>(pub T);
+
+impl<T: Serialize + Send + Sync + 'static> RequestInitialiser for DefaultQueryParams<T> {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.query(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientBuilder;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Order(Vec<&'static str>);
+
+    struct Push(&'static str);
+
+    impl RequestInitialiser for Push {
+        fn init(&self, mut req: RequestBuilder) -> RequestBuilder {
+            let order = req.extensions().get_or_insert_with(|| Order(vec![]));
+            order.0.push(self.0);
+            req
+        }
+
+        fn on_request(&self, _req: &Request, ext: &mut Extensions) {
+            ext.get_or_insert_with(|| Order(vec![])).0.push(self.0);
+        }
+    }
+
+    #[test]
+    fn request_stack_runs_outer_first() {
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(RequestStack::new(
+                Push("inner"),
+                RequestStack::new(Push("middle"), Push("outer")),
+            ))
+            .build();
+
+        let (req, mut ext) = client.get("http://localhost").build_with_extensions();
+        assert_eq!(ext.get(), Some(&Order(vec!["outer", "middle", "inner"])));
+
+        ext.clear();
+        let stack = RequestStack::new(Push("inner"), Push("outer"));
+        stack.on_request(&req.unwrap(), &mut ext);
+        assert_eq!(ext.get(), Some(&Order(vec!["outer", "inner"])));
+    }
+
+    #[tokio::test]
+    async fn request_stack_of_layers_wraps_outer_first() {
+        use tower::{service_fn, util::MapRequestLayer, ServiceExt};
+
+        fn push(name: &'static str) -> MapRequestLayer<impl Fn(Request) -> Request + Clone> {
+            MapRequestLayer::new(move |mut req: Request| {
+                req.headers_mut()
+                    .append("x-order", http::HeaderValue::from_static(name));
+                req
+            })
+        }
+
+        let service = RequestStack::new(push("inner"), push("outer")).layer(service_fn(
+            |req: Request| async move { Ok::<_, std::convert::Infallible>(req) },
+        ));
+        let req = Request::new(reqwest::Method::GET, "http://localhost".parse().unwrap());
+        let req = service.oneshot(req).await.unwrap();
+
+        let order: Vec<_> = req.headers().get_all("x-order").iter().collect();
+        assert_eq!(order, ["outer", "inner"]);
+    }
+}
@@ -0,0 +1,17 @@
+/// The number of times a request has been resent, stored in the request [`Extensions`].
+///
+/// Middleware that re-issues requests, such as `RetryTransientMiddleware` from `reqwest-retry`,
+/// inserts this extension before each attempt. Middleware further down the stack, such as
+/// `TracingMiddleware` from `reqwest-tracing`, can read it to tell a first attempt (`0`) apart
+/// from a retry.
+///
+/// [`Extensions`]: http::Extensions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResendCount(pub u32);
+
+impl ResendCount {
+    /// Returns the number of times the request has been resent.
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
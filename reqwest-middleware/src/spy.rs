@@ -0,0 +1,212 @@
+use std::sync::{Arc, Mutex};
+
+use http::{Extensions, HeaderMap, StatusCode, Version};
+use reqwest::{Request, Response, ResponseBuilderExt, Url};
+
+use crate::{Middleware, Next, Result};
+
+/// Records the requests going through a client and the responses they get, for tests.
+///
+/// Returned by [`ClientBuilder::build_testable`], which adds it as the last middleware of the
+/// client, so it sees requests exactly as they are sent, e.g. once per retry. Response bodies are
+/// buffered by the spy, which hands an identical response on to the rest of the middleware stack,
+/// keeping the extensions of the original, e.g. its [`remote_addr`](Response::remote_addr).
+/// Recorded responses don't have extensions.
+///
+/// [`ClientBuilder::build_testable`]: crate::ClientBuilder::build_testable
+#[derive(Clone, Debug, Default)]
+pub struct MiddlewareSpy {
+    records: Arc<Mutex<Records>>,
+}
+
+#[derive(Debug, Default)]
+struct Records {
+    requests: Vec<Request>,
+    responses: Vec<RecordedResponse>,
+}
+
+#[derive(Debug)]
+struct RecordedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    url: Url,
+    body: Vec<u8>,
+}
+
+impl RecordedResponse {
+    fn to_response(&self) -> Response {
+        let mut builder = http::Response::builder()
+            .status(self.status)
+            .version(self.version)
+            .url(self.url.clone());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = self.headers.clone();
+        }
+        builder
+            .body(self.body.clone())
+            .expect("status and headers come from a valid response")
+            .into()
+    }
+}
+
+impl MiddlewareSpy {
+    /// Returns copies of the requests seen so far, in the order they were sent.
+    ///
+    /// Streaming bodies can't be copied, so requests sent with one are returned without a body.
+    pub fn requests(&self) -> Vec<Request> {
+        let records = self.records.lock().unwrap();
+        records.requests.iter().map(copy_request).collect()
+    }
+
+    /// Returns copies of the responses received so far, in the order they were received.
+    pub fn responses(&self) -> Vec<Response> {
+        let records = self.records.lock().unwrap();
+        records
+            .responses
+            .iter()
+            .map(RecordedResponse::to_response)
+            .collect()
+    }
+}
+
+fn copy_request(req: &Request) -> Request {
+    req.try_clone().unwrap_or_else(|| {
+        let mut copy = Request::new(req.method().clone(), req.url().clone());
+        *copy.headers_mut() = req.headers().clone();
+        *copy.version_mut() = req.version();
+        *copy.timeout_mut() = req.timeout().copied();
+        copy
+    })
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for MiddlewareSpy {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        self.records
+            .lock()
+            .unwrap()
+            .requests
+            .push(copy_request(&req));
+
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut res = next.run(req, extensions).await?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let response_extensions = std::mem::take(res.extensions_mut());
+        let recorded = RecordedResponse {
+            status: res.status(),
+            version: res.version(),
+            headers: res.headers().clone(),
+            url: res.url().clone(),
+            body: res.bytes().await?.to_vec(),
+        };
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut res = recorded.to_response();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            *res.extensions_mut() = response_extensions;
+        }
+        self.records.lock().unwrap().responses.push(recorded);
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn records_requests_and_responses() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/echo"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .insert_header("x-spied", "yes")
+                    .set_body_string("pong"),
+            )
+            .mount(&server)
+            .await;
+        let (client, spy) = ClientBuilder::new(reqwest::Client::new()).build_testable();
+        let url = format!("{}/echo", server.uri());
+
+        let res = client
+            .post(&url)
+            .header("x-test", "1")
+            .body("ping")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.url().as_str(), url);
+        assert_eq!(res.headers()["x-spied"], "yes");
+        assert_eq!(res.text().await.unwrap(), "pong");
+
+        let requests = spy.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method(), "POST");
+        assert_eq!(requests[0].url().as_str(), url);
+        assert_eq!(requests[0].headers()["x-test"], "1");
+        assert_eq!(
+            requests[0].body().and_then(|body| body.as_bytes()),
+            Some(&b"ping"[..])
+        );
+
+        let mut responses = spy.responses();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].status(), StatusCode::CREATED);
+        assert_eq!(responses[0].url().as_str(), url);
+        assert_eq!(responses[0].headers()["x-spied"], "yes");
+        assert_eq!(responses.remove(0).text().await.unwrap(), "pong");
+    }
+
+    #[tokio::test]
+    async fn sees_requests_after_the_other_middleware() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let (client, spy) = ClientBuilder::new(reqwest::Client::new())
+            .with(crate::middleware_fn(|mut req, extensions, next| {
+                req.headers_mut()
+                    .insert("x-added", http::HeaderValue::from_static("by middleware"));
+                Box::pin(next.run(req, extensions))
+            }))
+            .build_testable();
+
+        client.get(server.uri()).send().await.unwrap();
+        client.get(server.uri()).send().await.unwrap();
+
+        let requests = spy.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|req| req.headers()["x-added"] == "by middleware"));
+        assert_eq!(spy.responses().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_extensions_of_the_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let (client, _spy) = ClientBuilder::new(reqwest::Client::new()).build_testable();
+
+        let res = client.get(server.uri()).send().await.unwrap();
+
+        assert_eq!(res.remote_addr(), Some(*server.address()));
+    }
+}
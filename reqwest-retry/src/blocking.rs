@@ -0,0 +1,186 @@
+//! Retries for the blocking [`reqwest::blocking::Client`].
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use reqwest::blocking::{Client, Request, Response};
+use reqwest_middleware::{Error, Result};
+use retry_policies::{RetryDecision, RetryPolicy};
+
+use crate::retryable_strategy::{DefaultRetryableStrategy, RetryableStrategy};
+use crate::{RetryError, Retryable};
+
+/// The blocking counterpart of [`RetryTransientMiddleware`], retrying requests sent with a
+/// [`reqwest::blocking::Client`] on transient errors.
+///
+/// `reqwest-middleware` only supports the async client, so this wraps the blocking client
+/// directly: requests are sent with [`execute`](Self::execute) and the calling thread sleeps
+/// between attempts. Like [`reqwest::blocking::Client`], it must not be used from within an async
+/// runtime.
+///
+/// The [`RetryableStrategy`] is given the status and headers of the responses, but not their body.
+///
+/// ```no_run
+/// use reqwest_retry::{policies::ExponentialBackoff, BlockingRetryTransientMiddleware};
+///
+/// let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+/// let client = BlockingRetryTransientMiddleware::new_with_policy(
+///     reqwest::blocking::Client::new(),
+///     retry_policy,
+/// );
+///
+/// let request = client.client().get("https://truelayer.com").build().unwrap();
+/// let response = client.execute(request).unwrap();
+/// ```
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+pub struct BlockingRetryTransientMiddleware<
+    T: RetryPolicy + Send + Sync + 'static,
+    R: RetryableStrategy + Send + Sync + 'static = DefaultRetryableStrategy,
+> {
+    client: Client,
+    retry_policy: T,
+    retryable_strategy: R,
+    #[cfg(feature = "tracing")]
+    retry_log_level: tracing::Level,
+}
+
+impl<T: RetryPolicy + Send + Sync> BlockingRetryTransientMiddleware<T, DefaultRetryableStrategy> {
+    /// Construct `BlockingRetryTransientMiddleware` with a [retry_policy][RetryPolicy].
+    pub fn new_with_policy(client: Client, retry_policy: T) -> Self {
+        Self::new_with_policy_and_strategy(client, retry_policy, DefaultRetryableStrategy)
+    }
+}
+
+impl<T, R> BlockingRetryTransientMiddleware<T, R>
+where
+    T: RetryPolicy + Send + Sync,
+    R: RetryableStrategy + Send + Sync,
+{
+    /// Construct `BlockingRetryTransientMiddleware` with a [retry_policy][RetryPolicy] and
+    /// [retryable_strategy](RetryableStrategy).
+    pub fn new_with_policy_and_strategy(
+        client: Client,
+        retry_policy: T,
+        retryable_strategy: R,
+    ) -> Self {
+        Self {
+            client,
+            retry_policy,
+            retryable_strategy,
+            #[cfg(feature = "tracing")]
+            retry_log_level: tracing::Level::WARN,
+        }
+    }
+
+    /// Set the log [level][tracing::Level] for retry events.
+    /// The default is [`WARN`][tracing::Level::WARN].
+    #[cfg(feature = "tracing")]
+    pub fn with_retry_log_level(mut self, level: tracing::Level) -> Self {
+        self.retry_log_level = level;
+        self
+    }
+
+    /// Returns a reference to the wrapped client, e.g. to build requests.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Sends `req`, retrying it on transient errors as long as the retry policy allows.
+    ///
+    /// # Errors
+    ///
+    /// Errors are reported the same way as by [`RetryTransientMiddleware`], as
+    /// [`Error::Middleware`] wrapping a [`RetryError`]. Requests with a streaming body can't be
+    /// retried and fail right away.
+    ///
+    /// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+    pub fn execute(&self, req: Request) -> Result<Response> {
+        let mut n_past_retries = 0;
+        let start_time = SystemTime::now();
+        loop {
+            let duplicate_request = req.try_clone().ok_or_else(|| {
+                Error::middleware_boxed(
+                    "Request object is not cloneable. Are you passing a streaming body?".into(),
+                )
+            })?;
+
+            let (retryable, result) = match self.client.execute(duplicate_request) {
+                Ok(response) => {
+                    let retryable = self.retryable_strategy.handle(&Ok(without_body(&response)));
+                    (retryable, Ok(response))
+                }
+                Err(err) => {
+                    let (retryable, err) = self.classify_error(err);
+                    (retryable, Err(err))
+                }
+            };
+
+            if let Some(Retryable::Transient) = retryable {
+                if let RetryDecision::Retry { execute_after } =
+                    self.retry_policy.should_retry(start_time, n_past_retries)
+                {
+                    let duration = execute_after
+                        .duration_since(SystemTime::now())
+                        .unwrap_or_else(|_| Duration::default());
+                    #[cfg(feature = "tracing")]
+                    log_retry!(
+                        self.retry_log_level,
+                        "Retry attempt #{}. Sleeping {:?} before the next attempt",
+                        n_past_retries,
+                        duration
+                    );
+                    thread::sleep(duration);
+                    n_past_retries += 1;
+                    continue;
+                }
+            }
+
+            // Report whether we gave up on a transient error after retrying, or failed with or
+            // without retries.
+            break if n_past_retries > 0 && retryable == Some(Retryable::Transient) {
+                result.map_err(|err| {
+                    Error::middleware(RetryError::RetriesExhausted {
+                        attempts: n_past_retries + 1,
+                        last_error: Box::new(err),
+                        total_elapsed: start_time.elapsed().unwrap_or_default(),
+                    })
+                })
+            } else if n_past_retries > 0 {
+                result.map_err(|err| {
+                    Error::middleware(RetryError::WithRetries {
+                        retries: n_past_retries,
+                        err,
+                    })
+                })
+            } else {
+                result.map_err(|err| Error::middleware(RetryError::Error(err)))
+            };
+        }
+    }
+
+    /// Asks the [`RetryableStrategy`] whether `err` is worth retrying, handing it back as the
+    /// error to report.
+    fn classify_error(&self, err: reqwest::Error) -> (Option<Retryable>, Error) {
+        // The strategy only borrows the result, so the error can be taken back out of it.
+        let result: Result<reqwest::Response> = Err(Error::Reqwest(err));
+        let retryable = self.retryable_strategy.handle(&result);
+        let Err(err) = result else {
+            unreachable!("the result is an error")
+        };
+        (retryable, err)
+    }
+}
+
+/// An async response with the status and headers of `response`, for the [`RetryableStrategy`].
+fn without_body(response: &Response) -> reqwest::Response {
+    let mut builder = http::Response::builder()
+        .status(response.status())
+        .version(response.version());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    builder
+        .body(Vec::<u8>::new())
+        .expect("status and headers come from a valid response")
+        .into()
+}
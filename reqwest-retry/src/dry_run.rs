@@ -0,0 +1,135 @@
+//! `DryRunRetryTransientMiddleware` records the retries `RetryTransientMiddleware` would perform.
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use retry_policies::{RetryDecision, RetryPolicy};
+
+use crate::retryable::Retryable;
+use crate::retryable_strategy::{DefaultRetryableStrategy, RetryableStrategy};
+
+/// A stand-in for [`RetryTransientMiddleware`] in tests, sending each request once and recording
+/// the attempts that would have been made, without retrying nor sleeping.
+///
+/// The outcome of the request is classified by the [`RetryableStrategy`], then, as long as it is
+/// [`Retryable::Transient`], the [`RetryPolicy`] is asked whether it would retry it once more,
+/// assuming that every attempt fails the same way. Each attempt is recorded as a
+/// [`RetryAttemptRecord`], so a request which would be retried twice records three attempts. The
+/// outcome of the request is returned as is.
+///
+/// Time doesn't pass during the dry run, so the policy is told that the request started as long
+/// ago as the sum of the delays it asked for, e.g. for time-bounded policies to stop. At most
+/// [`MAX_DRY_RUN_RETRIES`] retries are recorded for policies which never stop.
+///
+/// Create it with [`RetryTransientMiddleware::new_dry_run`].
+///
+/// ```
+/// use reqwest_middleware::ClientBuilder;
+/// use reqwest_retry::{
+///     policies::ExponentialBackoff, DefaultRetryableStrategy, RetryTransientMiddleware,
+/// };
+///
+/// let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+/// let dry_run = RetryTransientMiddleware::new_dry_run(retry_policy, DefaultRetryableStrategy);
+/// let records = dry_run.records();
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with(dry_run)
+///     .build();
+///
+/// // ... send requests with `client`, then inspect `records`
+/// assert!(records.lock().unwrap().is_empty());
+/// ```
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+/// [`RetryTransientMiddleware::new_dry_run`]: crate::RetryTransientMiddleware::new_dry_run
+pub struct DryRunRetryTransientMiddleware<
+    T: RetryPolicy + Send + Sync + 'static,
+    R: RetryableStrategy + Send + Sync + 'static = DefaultRetryableStrategy,
+> {
+    retry_policy: T,
+    retryable_strategy: R,
+    records: Arc<Mutex<Vec<RetryAttemptRecord>>>,
+}
+
+/// The maximum number of retries [`DryRunRetryTransientMiddleware`] records for a request.
+pub const MAX_DRY_RUN_RETRIES: u32 = 1_000;
+
+/// An attempt [`DryRunRetryTransientMiddleware`] would have made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RetryAttemptRecord {
+    /// The attempt number, starting from 1 for the request actually sent.
+    pub attempt: u32,
+    /// How the [`RetryableStrategy`] classified the outcome of the attempt, `None` if it succeeded.
+    pub retryable: Option<Retryable>,
+}
+
+impl<T, R> DryRunRetryTransientMiddleware<T, R>
+where
+    T: RetryPolicy + Send + Sync,
+    R: RetryableStrategy + Send + Sync,
+{
+    pub(crate) fn new(retry_policy: T, retryable_strategy: R) -> Self {
+        Self {
+            retry_policy,
+            retryable_strategy,
+            records: Default::default(),
+        }
+    }
+
+    /// The attempts recorded so far, for all the requests sent through the middleware.
+    pub fn records(&self) -> Arc<Mutex<Vec<RetryAttemptRecord>>> {
+        self.records.clone()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<T, R> Middleware for DryRunRetryTransientMiddleware<T, R>
+where
+    T: RetryPolicy + Send + Sync,
+    R: RetryableStrategy + Send + Sync + 'static,
+{
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let start_time = SystemTime::now();
+        let result = next.run(req, extensions).await;
+        let retryable = self.retryable_strategy.handle(&result);
+
+        let mut attempts = Vec::new();
+        let mut simulated_elapsed = Duration::ZERO;
+        let mut n_past_retries = 0;
+        loop {
+            attempts.push(RetryAttemptRecord {
+                attempt: n_past_retries + 1,
+                retryable,
+            });
+            if retryable != Some(Retryable::Transient) || n_past_retries >= MAX_DRY_RUN_RETRIES {
+                break;
+            }
+            let simulated_start = start_time
+                .checked_sub(simulated_elapsed)
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            match self
+                .retry_policy
+                .should_retry(simulated_start, n_past_retries)
+            {
+                RetryDecision::Retry { execute_after } => {
+                    simulated_elapsed += execute_after
+                        .duration_since(SystemTime::now())
+                        .unwrap_or_default();
+                    n_past_retries += 1;
+                }
+                RetryDecision::DoNotRetry => break,
+            }
+        }
+        self.records.lock().unwrap().extend(attempts);
+        result
+    }
+}
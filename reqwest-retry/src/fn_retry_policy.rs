@@ -0,0 +1,22 @@
+use std::time::SystemTime;
+
+use retry_policies::{RetryDecision, RetryPolicy};
+
+/// A [`RetryPolicy`] delegating to a closure, see
+/// [`RetryTransientMiddleware::new_with_policy_fn`].
+///
+/// The closure is called with the time the request was first started and the number of retries
+/// so far.
+///
+/// [`RetryTransientMiddleware::new_with_policy_fn`]: crate::RetryTransientMiddleware::new_with_policy_fn
+#[derive(Clone, Copy)]
+pub struct FnRetryPolicy<F>(pub F);
+
+impl<F> RetryPolicy for FnRetryPolicy<F>
+where
+    F: Fn(&SystemTime, u32) -> RetryDecision,
+{
+    fn should_retry(&self, request_start_time: SystemTime, n_past_retries: u32) -> RetryDecision {
+        (self.0)(&request_start_time, n_past_retries)
+    }
+}
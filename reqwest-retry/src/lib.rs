@@ -25,18 +25,60 @@
 //! }
 //! ```
 
+#[doc(hidden)]
+// We need this macro because tracing expects the level to be const:
+// https://github.com/tokio-rs/tracing/issues/2730
+#[cfg(feature = "tracing")]
+macro_rules! log_retry {
+    ($level:expr, $($args:tt)*) => {{
+        match $level {
+            ::tracing::Level::TRACE => ::tracing::trace!($($args)*),
+            ::tracing::Level::DEBUG => ::tracing::debug!($($args)*),
+            ::tracing::Level::INFO => ::tracing::info!($($args)*),
+            ::tracing::Level::WARN => ::tracing::warn!($($args)*),
+            ::tracing::Level::ERROR => ::tracing::error!($($args)*),
+        }
+    }};
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod blocking;
+mod dry_run;
+mod fn_retry_policy;
+mod method_retry_config;
 mod middleware;
+mod min_retries;
+mod request_builder_ext;
+mod request_id;
+mod retry_counter;
+mod retry_outcome;
 mod retryable;
 mod retryable_strategy;
 
-pub use retry_policies::{policies, Jitter, RetryDecision, RetryPolicy};
+use std::time::Duration;
+
 use thiserror::Error;
 
-pub use middleware::RetryTransientMiddleware;
-pub use retryable::Retryable;
+pub use retry_policies::{policies, Jitter, RetryDecision, RetryPolicy};
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use blocking::BlockingRetryTransientMiddleware;
+pub use dry_run::{DryRunRetryTransientMiddleware, RetryAttemptRecord, MAX_DRY_RUN_RETRIES};
+pub use fn_retry_policy::FnRetryPolicy;
+pub use method_retry_config::MethodRetryConfig;
+pub use middleware::{
+    RetryEvent, RetryTransientMiddleware, RetryTransientMiddlewareBuilder, SleepFuture,
+};
+pub use min_retries::{ExponentialBackoffBuilderExt, MinRetries};
+pub use request_builder_ext::RequestBuilderRetryExt;
+pub use request_id::RequestId;
+pub use retry_counter::{Counter, NullCounter, RetryLabels, RetryMetricOutcome, RetryUrlTemplate};
+pub use retry_outcome::{RetryFailureKind, RetryFinalResult, RetryOutcome};
+pub use retryable::{RetryDecisionOverride, Retryable};
 pub use retryable_strategy::{
-    default_on_request_failure, default_on_request_success, DefaultRetryableStrategy,
-    RetryableStrategy,
+    classify_io_error, default_on_request_failure, default_on_request_success, for_status_codes,
+    DefaultRetryableStrategy, HeaderControlledRetryStrategy, RetryAfterAwareFatalStrategy,
+    RetryableStrategy, TRANSIENT_IO_ERROR_KINDS,
 };
 
 /// Custom error type to attach the number of retries to the error message.
@@ -48,6 +90,18 @@ pub enum RetryError {
         #[source]
         err: reqwest_middleware::Error,
     },
+    /// The last attempt failed with a transient error, but the retry policy or the time limits
+    /// didn't allow any more attempts. Only returned once the request was retried at least once.
+    #[error("Request failed after {attempts} attempts in {total_elapsed:?}, giving up")]
+    RetriesExhausted {
+        /// Number of times the request was sent, including the first attempt.
+        attempts: u32,
+        /// The error of the last attempt.
+        #[source]
+        last_error: Box<reqwest_middleware::Error>,
+        /// Time elapsed between the start of the first attempt and the end of the last one.
+        total_elapsed: Duration,
+    },
     #[error(transparent)]
     Error(reqwest_middleware::Error),
 }
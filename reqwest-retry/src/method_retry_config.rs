@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reqwest::Method;
+use retry_policies::RetryPolicy;
+
+/// Per-method retry configuration for [`RetryTransientMiddleware`].
+///
+/// Requests whose method has a policy configured here are retried according to that policy,
+/// while all other requests use the policy [`RetryTransientMiddleware`] was constructed with.
+/// This allows, for example, retrying `GET` requests aggressively while keeping `POST`
+/// requests conservative.
+///
+/// ```rust
+/// use reqwest::Method;
+/// use reqwest_retry::{policies::ExponentialBackoff, MethodRetryConfig, RetryTransientMiddleware};
+///
+/// let config = MethodRetryConfig::new()
+///     .with_policy(Method::POST, ExponentialBackoff::builder().build_with_max_retries(1));
+///
+/// let retry_transient_middleware = RetryTransientMiddleware::new_with_policy(
+///     ExponentialBackoff::builder().build_with_max_retries(5),
+/// )
+/// .with_method_config(config);
+/// ```
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+#[derive(Clone, Default)]
+pub struct MethodRetryConfig {
+    policies: HashMap<Method, Option<Arc<dyn RetryPolicy + Send + Sync>>>,
+}
+
+impl MethodRetryConfig {
+    /// Creates an empty configuration, using the instance-level policy for every method.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retries requests with the given method according to `policy`.
+    pub fn with_policy<P>(self, method: Method, policy: P) -> Self
+    where
+        P: RetryPolicy + Send + Sync + 'static,
+    {
+        self.with_arc_policy(method, Some(Arc::new(policy)))
+    }
+
+    /// Sets the policy for requests with the given method. `None` means the instance-level
+    /// policy is used. [`with_policy`] is more ergonomic if you don't need the `Arc`.
+    ///
+    /// [`with_policy`]: Self::with_policy
+    pub fn with_arc_policy(
+        mut self,
+        method: Method,
+        policy: Option<Arc<dyn RetryPolicy + Send + Sync>>,
+    ) -> Self {
+        self.policies.insert(method, policy);
+        self
+    }
+
+    /// Returns the policy configured for `method`, if any.
+    pub(crate) fn policy_for(&self, method: &Method) -> Option<&(dyn RetryPolicy + Send + Sync)> {
+        self.policies.get(method)?.as_deref()
+    }
+}
@@ -1,29 +1,25 @@
 //! `RetryTransientMiddleware` implements retrying requests on transient errors.
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
 use crate::retryable_strategy::RetryableStrategy;
-use crate::{retryable::Retryable, retryable_strategy::DefaultRetryableStrategy, RetryError};
-use anyhow::anyhow;
+use crate::{
+    dry_run::DryRunRetryTransientMiddleware,
+    fn_retry_policy::FnRetryPolicy,
+    method_retry_config::MethodRetryConfig,
+    request_id::RequestId,
+    retry_counter::{Counter, NullCounter, RetryLabels, RetryMetricOutcome, RetryUrlTemplate},
+    retry_outcome::{RetryFailureKind, RetryFinalResult, RetryOutcome},
+    retryable::{RetryDecisionOverride, Retryable},
+    retryable_strategy::DefaultRetryableStrategy,
+    RetryError,
+};
 use http::Extensions;
-use reqwest::{Request, Response};
-use reqwest_middleware::{Error, Middleware, Next, Result};
-use retry_policies::RetryPolicy;
-
-#[doc(hidden)]
-// We need this macro because tracing expects the level to be const:
-// https://github.com/tokio-rs/tracing/issues/2730
-#[cfg(feature = "tracing")]
-macro_rules! log_retry {
-    ($level:expr, $($args:tt)*) => {{
-        match $level {
-            ::tracing::Level::TRACE => ::tracing::trace!($($args)*),
-            ::tracing::Level::DEBUG => ::tracing::debug!($($args)*),
-            ::tracing::Level::INFO => ::tracing::info!($($args)*),
-            ::tracing::Level::WARN => ::tracing::warn!($($args)*),
-            ::tracing::Level::ERROR => ::tracing::error!($($args)*),
-        }
-    }};
-}
+use reqwest::{Method, Request, Response, Url};
+use reqwest_middleware::{Error, Middleware, Next, ResendCount, Result};
+use retry_policies::{RetryDecision, RetryPolicy};
 
 /// `RetryTransientMiddleware` offers retry logic for requests that fail in a transient manner
 /// and can be safely executed again.
@@ -70,14 +66,89 @@ pub struct RetryTransientMiddleware<
 > {
     retry_policy: T,
     retryable_strategy: R,
+    method_config: MethodRetryConfig,
+    middleware_error_strategy: Option<MiddlewareErrorStrategy>,
+    on_retry: Option<OnRetry>,
+    max_elapsed: Option<Duration>,
+    sleep_fn: Option<SleepFn>,
+    pre_retry_hook: Option<PreRetryHook>,
+    #[cfg(feature = "jitter")]
+    jitter_factor: Option<f64>,
+    #[cfg(feature = "jitter")]
+    jitter_rng: Option<JitterRng>,
+    isolate_extensions: bool,
+    retry_counter: RetryCounter,
     #[cfg(feature = "tracing")]
     retry_log_level: tracing::Level,
 }
 
+type MiddlewareErrorStrategy = Arc<
+    dyn Fn(&(dyn std::error::Error + Send + Sync + 'static)) -> Option<Retryable> + Send + Sync,
+>;
+type OnRetry = Arc<dyn Fn(RetryEvent) + Send + Sync>;
+type SleepFn = Arc<dyn Fn(Duration) -> SleepFuture + Send + Sync>;
+type PreRetryHook = Arc<dyn Fn(&mut Request, &mut Extensions) + Send + Sync>;
+type RetryCounter = Arc<dyn Counter + Send + Sync>;
+#[cfg(feature = "jitter")]
+type JitterRng = Arc<std::sync::Mutex<rand::rngs::StdRng>>;
+
+/// The future returned by a custom sleep function, see [`RetryTransientMiddlewareBuilder::sleep_fn`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+/// The future returned by a custom sleep function, see [`RetryTransientMiddlewareBuilder::sleep_fn`].
+#[cfg(target_arch = "wasm32")]
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Describes a retry scheduled by [`RetryTransientMiddleware`], passed to the
+/// [`on_retry`](RetryTransientMiddlewareBuilder::on_retry) callback.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RetryEvent {
+    /// The method of the request being retried.
+    pub method: Method,
+    /// The URL of the request being retried.
+    pub url: Url,
+    /// How long the middleware waits before the next attempt.
+    pub delay: Duration,
+    /// The number of retries before the attempt that just failed, `0` when it was the first one.
+    pub n_past_retries: u32,
+    /// Whether the retry policy will refuse another retry after this one.
+    last_attempt: bool,
+}
+
+impl RetryEvent {
+    /// Returns `true` if the attempt that just failed was the first one, i.e. this is the first
+    /// retry of the request.
+    pub fn is_first_attempt(&self) -> bool {
+        self.n_past_retries == 0
+    }
+
+    /// Returns `true` if the upcoming attempt is the last one allowed by the retry policy.
+    ///
+    /// This is a speculative check: the policy is asked whether it would allow one more retry,
+    /// ahead of time. The retry may still happen later than the policy expects, and
+    /// [`RetryTransientMiddlewareBuilder::max_elapsed`] may stop the retries earlier.
+    pub fn is_last_attempt(&self) -> bool {
+        self.last_attempt
+    }
+
+    /// Returns the number of attempts made so far, including the one that just failed.
+    pub fn total_attempts_so_far(&self) -> u32 {
+        self.n_past_retries + 1
+    }
+}
+
 impl<T: RetryPolicy + Send + Sync> RetryTransientMiddleware<T, DefaultRetryableStrategy> {
     /// Construct `RetryTransientMiddleware` with  a [retry_policy][RetryPolicy].
+    ///
+    /// Use [`builder`](Self::builder) for more configuration options.
     pub fn new_with_policy(retry_policy: T) -> Self {
-        Self::new_with_policy_and_strategy(retry_policy, DefaultRetryableStrategy)
+        RetryTransientMiddlewareBuilder::new(retry_policy).build()
+    }
+
+    /// Start building a `RetryTransientMiddleware` with a [retry_policy][RetryPolicy].
+    pub fn builder(retry_policy: T) -> RetryTransientMiddlewareBuilder<T> {
+        RetryTransientMiddlewareBuilder::new(retry_policy)
     }
 
     /// Set the log [level][tracing::Level] for retry events.
@@ -89,6 +160,37 @@ impl<T: RetryPolicy + Send + Sync> RetryTransientMiddleware<T, DefaultRetryableS
     }
 }
 
+impl<F> RetryTransientMiddleware<FnRetryPolicy<F>, DefaultRetryableStrategy>
+where
+    F: Fn(&SystemTime, u32) -> RetryDecision + Send + Sync + 'static,
+{
+    /// Construct `RetryTransientMiddleware` with a closure deciding whether and when to retry,
+    /// instead of a [`RetryPolicy`].
+    ///
+    /// The closure is called with the time the request was first started and the number of
+    /// retries so far.
+    ///
+    ///```rust
+    ///     use std::time::{Duration, SystemTime};
+    ///     use reqwest_retry::{RetryDecision, RetryTransientMiddleware};
+    ///
+    ///     // Retry up to 3 times, waiting 1 second between attempts.
+    ///     let retry_transient_middleware =
+    ///         RetryTransientMiddleware::new_with_policy_fn(|_: &SystemTime, n_past_retries| {
+    ///             if n_past_retries < 3 {
+    ///                 RetryDecision::Retry {
+    ///                     execute_after: SystemTime::now() + Duration::from_secs(1),
+    ///                 }
+    ///             } else {
+    ///                 RetryDecision::DoNotRetry
+    ///             }
+    ///         });
+    ///```
+    pub fn new_with_policy_fn(policy_fn: F) -> Self {
+        Self::new_with_policy(FnRetryPolicy(policy_fn))
+    }
+}
+
 impl<T, R> RetryTransientMiddleware<T, R>
 where
     T: RetryPolicy + Send + Sync,
@@ -96,15 +198,310 @@ where
 {
     /// Construct `RetryTransientMiddleware` with  a [retry_policy][RetryPolicy] and [retryable_strategy](RetryableStrategy).
     pub fn new_with_policy_and_strategy(retry_policy: T, retryable_strategy: R) -> Self {
+        RetryTransientMiddlewareBuilder::new(retry_policy)
+            .retryable_strategy(retryable_strategy)
+            .build()
+    }
+
+    /// Construct a [`DryRunRetryTransientMiddleware`], recording the retries this middleware would
+    /// perform with the same [retry_policy][RetryPolicy] and
+    /// [retryable_strategy](RetryableStrategy), without performing them.
+    pub fn new_dry_run(
+        retry_policy: T,
+        retryable_strategy: R,
+    ) -> DryRunRetryTransientMiddleware<T, R> {
+        DryRunRetryTransientMiddleware::new(retry_policy, retryable_strategy)
+    }
+
+    /// Use a different [retry_policy][RetryPolicy] for some request methods, see
+    /// [`MethodRetryConfig`].
+    pub fn with_method_config(mut self, method_config: MethodRetryConfig) -> Self {
+        self.method_config = method_config;
+        self
+    }
+
+    /// Override how [`Error::Middleware`] errors are classified.
+    ///
+    /// By default, errors returned by other middleware are [`Retryable::Fatal`], unless created
+    /// from a retryable [`MiddlewareError`](reqwest_middleware::MiddlewareError). Some middleware
+    /// errors are transient though, e.g. a circuit breaker that is momentarily open, and can be
+    /// classified as such here. The [`RetryableStrategy`] is still used for all other outcomes.
+    pub fn with_middleware_error_strategy(
+        mut self,
+        middleware_error_strategy: MiddlewareErrorStrategy,
+    ) -> Self {
+        self.middleware_error_strategy = Some(middleware_error_strategy);
+        self
+    }
+
+    /// Multiply the delay computed by the retry policy by a random factor in
+    /// `[1 - jitter_factor, 1 + jitter_factor]`.
+    ///
+    /// This applies on top of any jitter of the retry policy itself. `jitter_factor` is clamped to
+    /// `[0.0, 1.0]`, a NaN factor disables the jitter.
+    #[cfg(feature = "jitter")]
+    pub fn with_jitter(mut self, jitter_factor: f64) -> Self {
+        self.jitter_factor = Some(clamp_jitter_factor(jitter_factor));
+        self
+    }
+
+    /// Draw the jitter of [`with_jitter`](Self::with_jitter) from a random number generator
+    /// seeded with `seed`, making the delays between attempts deterministic, e.g. in tests.
+    ///
+    /// Without a seed, the thread-local random number generator is used. The seeded generator is
+    /// shared by all the clones of this middleware. This doesn't affect the jitter of the retry
+    /// policy itself, use [`Jitter::None`](crate::Jitter::None) for deterministic delays.
+    #[cfg(feature = "jitter")]
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_rng = Some(seeded_rng(seed));
+        self
+    }
+
+    /// Cap the total time spent on a request, including all attempts and the delays between them,
+    /// whatever the retry policy.
+    ///
+    /// No retry is scheduled if its delay would end after `max_elapsed` has passed since the
+    /// first attempt: the result of the last attempt is returned right away instead. Note that
+    /// attempts themselves are not interrupted, use a request timeout for that.
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Discard the changes made to the request [`Extensions`] by attempts which are retried.
+    ///
+    /// By default, all attempts share the same extensions, so downstream middleware see (and
+    /// return) the values left by previous attempts. When enabled, the extensions are cloned
+    /// before each attempt and restored before retrying, so only the changes made by the last
+    /// attempt are returned to the caller. This clones all the extensions on every attempt, which
+    /// can be costly if they hold large values.
+    pub fn with_extensions_isolation_on_retry(mut self, isolate_extensions: bool) -> Self {
+        self.isolate_extensions = isolate_extensions;
+        self
+    }
+
+    /// Increment `counter` for every retry and for the final outcome of each request, e.g. to
+    /// export retry metrics to Prometheus, see [`Counter`].
+    pub fn with_retry_counter_metric(mut self, counter: RetryCounter) -> Self {
+        self.retry_counter = counter;
+        self
+    }
+}
+
+/// A builder for [`RetryTransientMiddleware`], gathering all of its configuration options.
+///
+///```rust
+///     use std::sync::Arc;
+///     use reqwest_middleware::ClientBuilder;
+///     use retry_policies::policies::ExponentialBackoff;
+///     use reqwest_retry::RetryTransientMiddleware;
+///     use reqwest::Client;
+///
+///     let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+///     let retry_transient_middleware = RetryTransientMiddleware::builder(retry_policy)
+///         .on_retry(Arc::new(|event| println!("Retrying {} in {:?}", event.url, event.delay)))
+///         .max_elapsed(std::time::Duration::from_secs(30))
+///         .build();
+///     let client = ClientBuilder::new(Client::new()).with(retry_transient_middleware).build();
+///```
+pub struct RetryTransientMiddlewareBuilder<
+    T: RetryPolicy + Send + Sync + 'static,
+    R: RetryableStrategy + Send + Sync + 'static = DefaultRetryableStrategy,
+> {
+    retry_policy: T,
+    retryable_strategy: R,
+    method_config: MethodRetryConfig,
+    middleware_error_strategy: Option<MiddlewareErrorStrategy>,
+    on_retry: Option<OnRetry>,
+    max_elapsed: Option<Duration>,
+    sleep_fn: Option<SleepFn>,
+    pre_retry_hook: Option<PreRetryHook>,
+    #[cfg(feature = "jitter")]
+    jitter_factor: Option<f64>,
+    #[cfg(feature = "jitter")]
+    jitter_rng: Option<JitterRng>,
+    isolate_extensions: bool,
+    retry_counter: RetryCounter,
+    #[cfg(feature = "tracing")]
+    retry_log_level: tracing::Level,
+}
+
+impl<T: RetryPolicy + Send + Sync> RetryTransientMiddlewareBuilder<T, DefaultRetryableStrategy> {
+    /// Start building a `RetryTransientMiddleware` with a [retry_policy][RetryPolicy] and the
+    /// [`DefaultRetryableStrategy`].
+    pub fn new(retry_policy: T) -> Self {
         Self {
             retry_policy,
-            retryable_strategy,
+            retryable_strategy: DefaultRetryableStrategy,
+            method_config: MethodRetryConfig::default(),
+            middleware_error_strategy: None,
+            on_retry: None,
+            max_elapsed: None,
+            sleep_fn: None,
+            pre_retry_hook: None,
+            #[cfg(feature = "jitter")]
+            jitter_factor: None,
+            #[cfg(feature = "jitter")]
+            jitter_rng: None,
+            isolate_extensions: false,
+            retry_counter: Arc::new(NullCounter),
             #[cfg(feature = "tracing")]
             retry_log_level: tracing::Level::WARN,
         }
     }
 }
 
+impl<T, R> RetryTransientMiddlewareBuilder<T, R>
+where
+    T: RetryPolicy + Send + Sync,
+    R: RetryableStrategy + Send + Sync,
+{
+    /// Use a custom [retryable_strategy](RetryableStrategy).
+    pub fn retryable_strategy<S>(
+        self,
+        retryable_strategy: S,
+    ) -> RetryTransientMiddlewareBuilder<T, S>
+    where
+        S: RetryableStrategy + Send + Sync,
+    {
+        RetryTransientMiddlewareBuilder {
+            retry_policy: self.retry_policy,
+            retryable_strategy,
+            method_config: self.method_config,
+            middleware_error_strategy: self.middleware_error_strategy,
+            on_retry: self.on_retry,
+            max_elapsed: self.max_elapsed,
+            sleep_fn: self.sleep_fn,
+            pre_retry_hook: self.pre_retry_hook,
+            #[cfg(feature = "jitter")]
+            jitter_factor: self.jitter_factor,
+            #[cfg(feature = "jitter")]
+            jitter_rng: self.jitter_rng,
+            isolate_extensions: self.isolate_extensions,
+            retry_counter: self.retry_counter,
+            #[cfg(feature = "tracing")]
+            retry_log_level: self.retry_log_level,
+        }
+    }
+
+    /// Set the log [level][tracing::Level] for retry events.
+    /// The default is [`WARN`][tracing::Level::WARN].
+    #[cfg(feature = "tracing")]
+    pub fn log_level(mut self, level: tracing::Level) -> Self {
+        self.retry_log_level = level;
+        self
+    }
+
+    /// Use a different [retry_policy][RetryPolicy] for some request methods, see
+    /// [`MethodRetryConfig`].
+    pub fn method_config(mut self, method_config: MethodRetryConfig) -> Self {
+        self.method_config = method_config;
+        self
+    }
+
+    /// Override how [`Error::Middleware`] errors are classified, see
+    /// [`RetryTransientMiddleware::with_middleware_error_strategy`].
+    pub fn middleware_error_strategy(
+        mut self,
+        middleware_error_strategy: MiddlewareErrorStrategy,
+    ) -> Self {
+        self.middleware_error_strategy = Some(middleware_error_strategy);
+        self
+    }
+
+    /// Call `on_retry` whenever a retry is scheduled, before sleeping.
+    pub fn on_retry(mut self, on_retry: OnRetry) -> Self {
+        self.on_retry = Some(on_retry);
+        self
+    }
+
+    /// Cap the total time spent on a request, including the delays between attempts, see
+    /// [`RetryTransientMiddleware::with_max_elapsed`].
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Cap the total time spent on a request, including the delays between attempts. The same as
+    /// [`max_elapsed`](Self::max_elapsed).
+    ///
+    /// The cap is checked before sleeping rather than after: a retry whose delay would end after
+    /// `total_timeout` is not scheduled at all, and the result of the last attempt is returned
+    /// right away instead of once the delay is over.
+    pub fn with_total_timeout(self, total_timeout: Duration) -> Self {
+        self.max_elapsed(total_timeout)
+    }
+
+    /// Use a custom function to sleep between attempts, e.g. to skip waiting in tests.
+    ///
+    /// By default, the middleware sleeps using [`tokio::time::sleep`] (or `wasmtimer` on
+    /// `wasm32`).
+    pub fn sleep_fn(
+        mut self,
+        sleep_fn: Arc<dyn Fn(Duration) -> SleepFuture + Send + Sync>,
+    ) -> Self {
+        self.sleep_fn = Some(sleep_fn);
+        self
+    }
+
+    /// Call `pre_retry_hook` on the request and its extensions before each retry, e.g. to
+    /// refresh a credential. It is not called before the first attempt.
+    pub fn pre_retry_hook(mut self, pre_retry_hook: PreRetryHook) -> Self {
+        self.pre_retry_hook = Some(pre_retry_hook);
+        self
+    }
+
+    /// Randomise the delay between attempts, see [`RetryTransientMiddleware::with_jitter`].
+    #[cfg(feature = "jitter")]
+    pub fn jitter(mut self, jitter_factor: f64) -> Self {
+        self.jitter_factor = Some(clamp_jitter_factor(jitter_factor));
+        self
+    }
+
+    /// Make the jitter deterministic, see [`RetryTransientMiddleware::with_jitter_seed`].
+    #[cfg(feature = "jitter")]
+    pub fn jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_rng = Some(seeded_rng(seed));
+        self
+    }
+
+    /// Discard the changes made to the request extensions by attempts which are retried, see
+    /// [`RetryTransientMiddleware::with_extensions_isolation_on_retry`].
+    pub fn extensions_isolation_on_retry(mut self, isolate_extensions: bool) -> Self {
+        self.isolate_extensions = isolate_extensions;
+        self
+    }
+
+    /// Count retries and request outcomes, see
+    /// [`RetryTransientMiddleware::with_retry_counter_metric`].
+    pub fn retry_counter_metric(mut self, counter: RetryCounter) -> Self {
+        self.retry_counter = counter;
+        self
+    }
+
+    /// Returns a `RetryTransientMiddleware` using this builder configuration.
+    pub fn build(self) -> RetryTransientMiddleware<T, R> {
+        RetryTransientMiddleware {
+            retry_policy: self.retry_policy,
+            retryable_strategy: self.retryable_strategy,
+            method_config: self.method_config,
+            middleware_error_strategy: self.middleware_error_strategy,
+            on_retry: self.on_retry,
+            max_elapsed: self.max_elapsed,
+            sleep_fn: self.sleep_fn,
+            pre_retry_hook: self.pre_retry_hook,
+            #[cfg(feature = "jitter")]
+            jitter_factor: self.jitter_factor,
+            #[cfg(feature = "jitter")]
+            jitter_rng: self.jitter_rng,
+            isolate_extensions: self.isolate_extensions,
+            retry_counter: self.retry_counter,
+            #[cfg(feature = "tracing")]
+            retry_log_level: self.retry_log_level,
+        }
+    }
+}
+
 #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 impl<T, R> Middleware for RetryTransientMiddleware<T, R>
@@ -118,10 +515,8 @@ where
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> Result<Response> {
-        // TODO: Ideally we should create a new instance of the `Extensions` map to pass
-        // downstream. This will guard against previous retries polluting `Extensions`.
-        // That is, we only return what's populated in the typemap for the last retry attempt
-        // and copy those into the the `global` Extensions map.
+        // Unless `with_extensions_isolation_on_retry` is enabled, all attempts share the same
+        // `Extensions`, so previous attempts may pollute them.
         self.execute_with_retry(req, next, extensions).await
     }
 }
@@ -140,6 +535,12 @@ where
         next: Next<'a>,
         ext: &'a mut Extensions,
     ) -> Result<Response> {
+        let retry_policy: &(dyn RetryPolicy + Send + Sync) = self
+            .method_config
+            .policy_for(req.method())
+            .unwrap_or(&self.retry_policy);
+        // All attempts share the same request id, so they can be correlated.
+        ext.get_or_insert_with(RequestId::new);
         let mut n_past_retries = 0;
         let start_time = SystemTime::now();
         loop {
@@ -147,57 +548,200 @@ where
             // However, if the body of the request is not static, e.g of type `Bytes`,
             // the Clone operation should be of constant complexity and not O(N)
             // since the byte abstraction is a shared pointer over a buffer.
-            let duplicate_request = req.try_clone().ok_or_else(|| {
-                Error::Middleware(anyhow!(
-                    "Request object is not cloneable. Are you passing a streaming body?"
-                        .to_string()
-                ))
+            let mut duplicate_request = req.try_clone().ok_or_else(|| {
+                Error::middleware_boxed(
+                    "Request object is not cloneable. Are you passing a streaming body?".into(),
+                )
             })?;
 
+            // Snapshot the extensions, to undo the changes of this attempt if it is retried.
+            let original_ext = if self.isolate_extensions {
+                Some(ext.clone())
+            } else {
+                None
+            };
+
+            if n_past_retries > 0 {
+                if let Some(pre_retry_hook) = &self.pre_retry_hook {
+                    pre_retry_hook(&mut duplicate_request, ext);
+                }
+            }
+
+            // Let downstream middleware (e.g. tracing) know how many times this request has
+            // already been sent.
+            ext.insert(ResendCount(n_past_retries));
+
             let result = next.clone().run(duplicate_request, ext).await;
 
             // We classify the response which will return None if not
-            // errors were returned.
-            if let Some(Retryable::Transient) = self.retryable_strategy.handle(&result) {
+            // errors were returned, unless the caller overrode the decision for this request.
+            let retryable = if let Some(RetryDecisionOverride(retryable)) = ext.get() {
+                *retryable
+            } else {
+                match (&result, &self.middleware_error_strategy) {
+                    (Err(Error::Middleware(err)), Some(strategy)) => strategy(err.as_ref()),
+                    _ => self.retryable_strategy.handle(&result),
+                }
+            };
+            if let Some(Retryable::Transient) = retryable {
                 // If the response failed and the error type was transient
                 // we can safely try to retry the request.
-                let retry_decision = self.retry_policy.should_retry(start_time, n_past_retries);
+                let retry_decision = retry_policy.should_retry(start_time, n_past_retries);
                 if let retry_policies::RetryDecision::Retry { execute_after } = retry_decision {
                     let duration = execute_after
                         .duration_since(SystemTime::now())
                         .unwrap_or_else(|_| Duration::default());
-                    // Sleep the requested amount before we try again.
-                    #[cfg(feature = "tracing")]
-                    log_retry!(
-                        self.retry_log_level,
-                        "Retry attempt #{}. Sleeping {:?} before the next attempt",
-                        n_past_retries,
-                        duration
-                    );
-                    #[cfg(not(target_arch = "wasm32"))]
-                    tokio::time::sleep(duration).await;
-                    #[cfg(target_arch = "wasm32")]
-                    wasmtimer::tokio::sleep(duration).await;
-
-                    n_past_retries += 1;
-                    continue;
+                    #[cfg(feature = "jitter")]
+                    let duration = match self.jitter_factor {
+                        Some(jitter_factor) => duration
+                            .mul_f64(1.0 - jitter_factor + 2.0 * jitter_factor * self.random()),
+                        None => duration,
+                    };
+                    let elapsed = start_time.elapsed().unwrap_or_default();
+                    let exceeds_max_elapsed = self
+                        .max_elapsed
+                        .is_some_and(|max_elapsed| elapsed + duration > max_elapsed);
+                    if !exceeds_max_elapsed {
+                        self.increment_retry_counter(
+                            n_past_retries,
+                            RetryMetricOutcome::Retried,
+                            ext,
+                        );
+                        if let Some(on_retry) = &self.on_retry {
+                            // Speculatively ask the policy about the retry after this one.
+                            let last_attempt = matches!(
+                                retry_policy.should_retry(start_time, n_past_retries + 1),
+                                retry_policies::RetryDecision::DoNotRetry
+                            );
+                            on_retry(RetryEvent {
+                                method: req.method().clone(),
+                                url: req.url().clone(),
+                                delay: duration,
+                                n_past_retries,
+                                last_attempt,
+                            });
+                        }
+                        // Sleep the requested amount before we try again.
+                        #[cfg(feature = "tracing")]
+                        log_retry!(
+                            self.retry_log_level,
+                            "Retry attempt #{}. Sleeping {:?} before the next attempt",
+                            n_past_retries,
+                            duration
+                        );
+                        if let Some(sleep_fn) = &self.sleep_fn {
+                            sleep_fn(duration).await;
+                        } else {
+                            #[cfg(not(target_arch = "wasm32"))]
+                            tokio::time::sleep(duration).await;
+                            #[cfg(target_arch = "wasm32")]
+                            wasmtimer::tokio::sleep(duration).await;
+                        }
+
+                        if let Some(original_ext) = original_ext {
+                            *ext = original_ext;
+                        }
+                        n_past_retries += 1;
+                        continue;
+                    }
                 }
             };
 
-            // Report whether we failed with or without retries.
-            break if n_past_retries > 0 {
+            let total_elapsed = start_time.elapsed().unwrap_or_default();
+            // Errors are failures even if they were not classified, e.g. because of a
+            // `RetryDecisionOverride(None)`.
+            let final_result = match (retryable, &result) {
+                (None, Ok(_)) => RetryFinalResult::Success,
+                (Some(Retryable::Transient), _) => {
+                    RetryFinalResult::Failed(RetryFailureKind::RetriesExhausted)
+                }
+                (Some(Retryable::Fatal), _) | (None, Err(_)) => {
+                    RetryFinalResult::Failed(RetryFailureKind::Fatal)
+                }
+            };
+            ext.insert(RetryOutcome {
+                attempts: n_past_retries + 1,
+                total_elapsed,
+                final_result,
+            });
+            self.increment_retry_counter(
+                n_past_retries,
+                match final_result {
+                    RetryFinalResult::Success => RetryMetricOutcome::Succeeded,
+                    RetryFinalResult::Failed(kind) => RetryMetricOutcome::Failed(kind),
+                },
+                ext,
+            );
+
+            // Report whether we gave up on a transient error after retrying, or failed with or
+            // without retries.
+            break if n_past_retries > 0 && retryable == Some(Retryable::Transient) {
                 result.map_err(|err| {
-                    Error::Middleware(
-                        RetryError::WithRetries {
-                            retries: n_past_retries,
-                            err,
-                        }
-                        .into(),
-                    )
+                    Error::middleware(RetryError::RetriesExhausted {
+                        attempts: n_past_retries + 1,
+                        last_error: Box::new(err),
+                        total_elapsed,
+                    })
+                })
+            } else if n_past_retries > 0 {
+                result.map_err(|err| {
+                    Error::middleware(RetryError::WithRetries {
+                        retries: n_past_retries,
+                        err,
+                    })
                 })
             } else {
-                result.map_err(|err| Error::Middleware(RetryError::Error(err).into()))
+                result.map_err(|err| Error::middleware(RetryError::Error(err)))
             };
         }
     }
+
+    fn increment_retry_counter(
+        &self,
+        n_past_retries: u32,
+        outcome: RetryMetricOutcome,
+        ext: &Extensions,
+    ) {
+        self.retry_counter.increment(&RetryLabels {
+            attempt_number: n_past_retries + 1,
+            outcome,
+            url_template: ext
+                .get::<RetryUrlTemplate>()
+                .map(|RetryUrlTemplate(template)| template.clone()),
+        });
+    }
+
+    /// A random number in `[0, 1)`, drawn from the seeded generator if any.
+    #[cfg(feature = "jitter")]
+    fn random(&self) -> f64 {
+        use rand::Rng;
+
+        match &self.jitter_rng {
+            Some(rng) => rng
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .gen(),
+            None => rand::random(),
+        }
+    }
+}
+
+#[cfg(feature = "jitter")]
+fn seeded_rng(seed: u64) -> JitterRng {
+    use rand::SeedableRng;
+
+    Arc::new(std::sync::Mutex::new(rand::rngs::StdRng::seed_from_u64(
+        seed,
+    )))
+}
+
+/// Clamps a jitter factor to `[0.0, 1.0]`, NaN disabling the jitter rather than turning every
+/// delay into NaN.
+#[cfg(feature = "jitter")]
+fn clamp_jitter_factor(jitter_factor: f64) -> f64 {
+    if jitter_factor.is_nan() {
+        0.0
+    } else {
+        jitter_factor.clamp(0.0, 1.0)
+    }
 }
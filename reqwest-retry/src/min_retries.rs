@@ -0,0 +1,90 @@
+use std::time::{Duration, SystemTime};
+
+use retry_policies::policies::{
+    ExponentialBackoff, ExponentialBackoffBuilder, ExponentialBackoffTimed,
+};
+use retry_policies::{RetryDecision, RetryPolicy};
+
+/// A [`RetryPolicy`] retrying at least `min_retries` times, even when the wrapped policy would stop
+/// sooner, e.g. because its total retry duration has elapsed.
+///
+/// The retries the wrapped policy refuses are delayed with an [`ExponentialBackoff`]. Note that
+/// [`RetryTransientMiddlewareBuilder::max_elapsed`] still stops the retries.
+///
+/// [`RetryTransientMiddlewareBuilder::max_elapsed`]: crate::RetryTransientMiddlewareBuilder::max_elapsed
+#[derive(Clone, Copy, Debug)]
+pub struct MinRetries<P> {
+    policy: P,
+    backoff: ExponentialBackoff,
+}
+
+impl<P> MinRetries<P> {
+    /// Wraps `policy`, delaying the retries it refuses with the default [`ExponentialBackoff`].
+    pub fn new(policy: P, min_retries: u32) -> Self {
+        MinRetries {
+            policy,
+            backoff: ExponentialBackoff::builder().build_with_max_retries(min_retries),
+        }
+    }
+
+    /// The minimum number of retries.
+    pub fn min_retries(&self) -> u32 {
+        self.backoff.max_n_retries.unwrap_or_default()
+    }
+}
+
+impl<P: RetryPolicy> RetryPolicy for MinRetries<P> {
+    fn should_retry(&self, request_start_time: SystemTime, n_past_retries: u32) -> RetryDecision {
+        match self.policy.should_retry(request_start_time, n_past_retries) {
+            // Retries up to `min_retries`, which is the maximum of the fallback backoff.
+            RetryDecision::DoNotRetry => self
+                .backoff
+                .should_retry(request_start_time, n_past_retries),
+            retry => retry,
+        }
+    }
+}
+
+/// Extends [`ExponentialBackoffBuilder`] with policies from this crate.
+pub trait ExponentialBackoffBuilderExt {
+    /// Builds an exponential backoff retrying for up to `total_duration`, like
+    /// [`ExponentialBackoffBuilder::build_with_total_retry_duration`], but at least `min_retries`
+    /// times regardless of the duration.
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use reqwest_retry::policies::ExponentialBackoff;
+    /// use reqwest_retry::{ExponentialBackoffBuilderExt, RetryDecision, RetryPolicy};
+    ///
+    /// let policy = ExponentialBackoff::builder()
+    ///     .build_with_min_retries(3, Duration::from_secs(60));
+    ///
+    /// // The total duration elapsed, but the request was only retried twice.
+    /// let started_at = SystemTime::now() - Duration::from_secs(120);
+    /// assert!(matches!(policy.should_retry(started_at, 2), RetryDecision::Retry { .. }));
+    /// assert!(matches!(policy.should_retry(started_at, 3), RetryDecision::DoNotRetry));
+    /// ```
+    fn build_with_min_retries(
+        self,
+        min_retries: u32,
+        total_duration: Duration,
+    ) -> MinRetries<ExponentialBackoffTimed>;
+}
+
+impl ExponentialBackoffBuilderExt for ExponentialBackoffBuilder {
+    fn build_with_min_retries(
+        self,
+        min_retries: u32,
+        total_duration: Duration,
+    ) -> MinRetries<ExponentialBackoffTimed> {
+        // The builder can only be consumed once, rebuild it from the bounded backoff.
+        let backoff = self.build_with_max_retries(min_retries);
+        let policy = ExponentialBackoff::builder()
+            .retry_bounds(backoff.min_retry_interval, backoff.max_retry_interval)
+            .jitter(backoff.jitter)
+            .base(backoff.base)
+            .build_with_total_retry_duration(total_duration);
+        MinRetries { policy, backoff }
+    }
+}
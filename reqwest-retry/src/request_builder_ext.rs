@@ -0,0 +1,51 @@
+use reqwest::Response;
+use reqwest_middleware::{ClientBuilder, RequestBuilder, Result};
+use retry_policies::RetryPolicy;
+
+use crate::RetryTransientMiddleware;
+
+/// Extends [`RequestBuilder`] to retry a single request, without attaching a
+/// [`RetryTransientMiddleware`] to the whole client.
+///
+/// ```rust
+/// use reqwest_middleware::ClientBuilder;
+/// use reqwest_retry::{policies::ExponentialBackoff, RequestBuilderRetryExt};
+///
+/// async fn run_retries() {
+///     let client = ClientBuilder::new(reqwest::Client::new()).build();
+///
+///     // Only this request is retried, up to 3 times.
+///     client
+///         .get("https://truelayer.com")
+///         .send_retry(ExponentialBackoff::builder().build_with_max_retries(3))
+///         .await
+///         .unwrap();
+/// }
+/// ```
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait RequestBuilderRetryExt {
+    /// Like [`RequestBuilder::send`], but retries transient failures according to `retry_policy`.
+    ///
+    /// The retries run after the client's own middleware, as if a
+    /// [`RetryTransientMiddleware`] was attached last.
+    async fn send_retry<T>(self, retry_policy: T) -> Result<Response>
+    where
+        T: RetryPolicy + Send + Sync + 'static;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl RequestBuilderRetryExt for RequestBuilder {
+    async fn send_retry<T>(mut self, retry_policy: T) -> Result<Response>
+    where
+        T: RetryPolicy + Send + Sync + 'static,
+    {
+        let mut extensions = std::mem::take(self.extensions());
+        let (client, req) = self.build_split();
+        let client = ClientBuilder::from_client(client)
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+        client.execute_with_extensions(req?, &mut extensions).await
+    }
+}
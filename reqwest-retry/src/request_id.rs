@@ -0,0 +1,29 @@
+use uuid::Uuid;
+
+/// Identifies a logical request, stable across retries.
+///
+/// [`RetryTransientMiddleware`] inserts a new `RequestId` in the request [`Extensions`] before
+/// the first attempt, and all the retries of that request share it. Middleware further down the
+/// stack can use it to correlate the attempts of a request, e.g. in log lines.
+///
+/// If a `RequestId` is already present in the extensions, e.g. because it was inserted by an
+/// upstream middleware or with [`RequestBuilder::with_extension`], it is kept as is.
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+/// [`Extensions`]: http::Extensions
+/// [`RequestBuilder::with_extension`]: reqwest_middleware::RequestBuilder::with_extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(pub Uuid);
+
+impl RequestId {
+    /// Generates a new random `RequestId`.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
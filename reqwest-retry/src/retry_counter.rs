@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+
+use crate::retry_outcome::RetryFailureKind;
+
+/// A counter incremented by [`RetryTransientMiddleware`] for every retry and final outcome of a
+/// request, see [`RetryTransientMiddleware::with_retry_counter_metric`].
+///
+/// It lets any metrics library count retries, e.g. by incrementing a Prometheus counter with the
+/// [`RetryLabels`] as labels, without this crate depending on it.
+///
+/// ```
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// use reqwest_retry::{Counter, RetryLabels, RetryMetricOutcome};
+///
+/// #[derive(Default)]
+/// struct RetryCount(AtomicU64);
+///
+/// impl Counter for RetryCount {
+///     fn increment(&self, labels: &RetryLabels) {
+///         if labels.outcome == RetryMetricOutcome::Retried {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+/// [`RetryTransientMiddleware::with_retry_counter_metric`]: crate::RetryTransientMiddleware::with_retry_counter_metric
+pub trait Counter {
+    fn increment(&self, labels: &RetryLabels);
+}
+
+/// A [`Counter`] doing nothing, used when no counter is configured.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullCounter;
+
+impl Counter for NullCounter {
+    fn increment(&self, _labels: &RetryLabels) {}
+}
+
+/// The labels a [`Counter`] is incremented with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RetryLabels {
+    /// The attempt the counter is incremented for, starting from 1 for the first attempt.
+    pub attempt_number: u32,
+    /// Whether the attempt is retried, or how the request ended.
+    pub outcome: RetryMetricOutcome,
+    /// The templated path of the request, taken from the [`RetryUrlTemplate`] extension.
+    pub url_template: Option<Cow<'static, str>>,
+}
+
+/// What happened after an attempt, see [`RetryLabels`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryMetricOutcome {
+    /// The attempt failed with a transient error and the request is retried.
+    Retried,
+    /// The attempt succeeded, ending the request.
+    Succeeded,
+    /// The attempt failed, ending the request.
+    Failed(RetryFailureKind),
+}
+
+/// The templated path of a request, e.g. `/payment/{paymentId}`, reported to the [`Counter`] in
+/// [`RetryLabels::url_template`].
+///
+/// Insert it in the request extensions, e.g. with [`RequestBuilder::with_extension`], to keep
+/// the cardinality of the metrics low. Requests without it are counted without a template.
+///
+/// [`RequestBuilder::with_extension`]: reqwest_middleware::RequestBuilder::with_extension
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryUrlTemplate(pub Cow<'static, str>);
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+/// Summary of all the attempts made for a request by [`RetryTransientMiddleware`].
+///
+/// It is inserted in the request [`Extensions`] once the middleware is done with the request,
+/// whether it succeeded or not, so that upstream middleware (e.g. for telemetry) can inspect it
+/// after calling [`Next::run`].
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+/// [`Extensions`]: http::Extensions
+/// [`Next::run`]: reqwest_middleware::Next::run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryOutcome {
+    /// Number of times the request was sent, including the first attempt.
+    pub attempts: u32,
+    /// Time elapsed between the start of the first attempt and the end of the last one,
+    /// including the time spent waiting between attempts.
+    pub total_elapsed: Duration,
+    /// How the last attempt was classified.
+    pub final_result: RetryFinalResult,
+}
+
+/// How the last attempt of a request was classified, see [`RetryOutcome`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryFinalResult {
+    /// The last attempt returned a response, which was not deemed an error by the
+    /// [`RetryableStrategy`].
+    ///
+    /// [`RetryableStrategy`]: crate::RetryableStrategy
+    Success,
+    /// The last attempt failed, either with an error or with a response deemed an error.
+    Failed(RetryFailureKind),
+}
+
+/// Why a request ultimately failed, see [`RetryFinalResult`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryFailureKind {
+    /// The last attempt failed with an error that should not be retried, or with an error that
+    /// was not classified at all.
+    Fatal,
+    /// The last attempt failed with a transient error, but the retry policy or the maximum
+    /// elapsed time didn't allow any more attempts.
+    RetriesExhausted,
+}
@@ -1,8 +1,11 @@
-use crate::retryable_strategy::{DefaultRetryableStrategy, RetryableStrategy};
+use crate::retryable_strategy::{
+    classify_io_error, default_on_request_failure, default_on_request_success,
+    DefaultRetryableStrategy, RetryableStrategy,
+};
 use reqwest_middleware::Error;
 
 /// Classification of an error/status returned by request.
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Retryable {
     /// The failure was due to something that might resolve in the future.
     Transient,
@@ -18,8 +21,60 @@ impl Retryable {
     pub fn from_reqwest_response(res: &Result<reqwest::Response, Error>) -> Option<Self> {
         DefaultRetryableStrategy.handle(res)
     }
+
+    /// Map a successful `reqwest` response into `Retryable`, like [`from_reqwest_response`] does
+    /// for `Ok` results.
+    ///
+    /// Returns `None` if the response status is not an error.
+    ///
+    /// [`from_reqwest_response`]: Self::from_reqwest_response
+    pub fn from_response(res: &reqwest::Response) -> Option<Self> {
+        default_on_request_success(res)
+    }
+
+    /// Map a request error into `Retryable`, like [`from_reqwest_response`] does for `Err`
+    /// results.
+    ///
+    /// [`from_reqwest_response`]: Self::from_reqwest_response
+    pub fn from_error(err: &Error) -> Option<Self> {
+        default_on_request_failure(err)
+    }
+
+    /// Map an IO error into `Retryable`, see [`classify_io_error`].
+    pub fn from_io_error(err: &std::io::Error) -> Self {
+        classify_io_error(err)
+    }
 }
 
+/// Overrides the [`RetryableStrategy`] decision of [`RetryTransientMiddleware`] for a single
+/// request when present in the request [`Extensions`].
+///
+/// This is a power-user escape hatch for when application logic knows better than the strategy,
+/// e.g. a payment idempotency window has expired and the request must never be retried:
+///
+/// - `RetryDecisionOverride(None)` and `RetryDecisionOverride(Some(Retryable::Fatal))` disable
+///   retries altogether.
+/// - `RetryDecisionOverride(Some(Retryable::Transient))` retries every attempt, whatever its
+///   outcome, until the retry policy gives up. Successful responses are retried too.
+///
+/// The retry policy is still consulted to decide whether and when another attempt is made.
+///
+/// ```
+/// use reqwest_middleware::ClientWithMiddleware;
+/// use reqwest_retry::RetryDecisionOverride;
+///
+/// # fn run(client: ClientWithMiddleware) {
+/// let request = client
+///     .post("https://truelayer.com/payments")
+///     .with_extension(RetryDecisionOverride(None));
+/// # }
+/// ```
+///
+/// [`RetryTransientMiddleware`]: crate::RetryTransientMiddleware
+/// [`Extensions`]: http::Extensions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryDecisionOverride(pub Option<Retryable>);
+
 impl From<&reqwest::Error> for Retryable {
     fn from(_status: &reqwest::Error) -> Retryable {
         Retryable::Transient
@@ -1,3 +1,6 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
 use crate::retryable::Retryable;
 use http::StatusCode;
 use reqwest_middleware::Error;
@@ -7,10 +10,10 @@ use reqwest_middleware::Error;
 /// A [`RetryableStrategy`] has a single `handler` functions.
 /// The result of calling the request could be:
 /// - [`reqwest::Response`] In case the request has been sent and received correctly
-///     This could however still mean that the server responded with a erroneous response.
-///     For example a HTTP statuscode of 500
+///   This could however still mean that the server responded with a erroneous response.
+///   For example a HTTP statuscode of 500
 /// - [`reqwest_middleware::Error`] In this case the request actually failed.
-///     This could, for example, be caused by a timeout on the connection.
+///   This could, for example, be caused by a timeout on the connection.
 ///
 /// Example:
 ///
@@ -103,6 +106,160 @@ impl RetryableStrategy for DefaultRetryableStrategy {
     }
 }
 
+/// A [`RetryableStrategy`] letting the server opt out of retries with an `X-Retry-Allowed: false`
+/// response header.
+///
+/// Failed responses carrying the header are [`Retryable::Fatal`] whatever their status code,
+/// e.g. a `503` for an operation the server knows is not safe to repeat. Successful responses
+/// and any other response or request failure are classified by [`DefaultRetryableStrategy`].
+///
+/// ```
+/// use reqwest_retry::{
+///     policies::ExponentialBackoff, HeaderControlledRetryStrategy, RetryTransientMiddleware,
+/// };
+///
+/// let retry_policy = ExponentialBackoff::builder().build_with_max_retries(2);
+/// let middleware = RetryTransientMiddleware::new_with_policy_and_strategy(
+///     retry_policy,
+///     HeaderControlledRetryStrategy,
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeaderControlledRetryStrategy;
+
+impl HeaderControlledRetryStrategy {
+    /// The response header checked by the strategy.
+    pub const HEADER: &'static str = "x-retry-allowed";
+}
+
+impl RetryableStrategy for HeaderControlledRetryStrategy {
+    fn handle(&self, res: &Result<reqwest::Response, Error>) -> Option<Retryable> {
+        let retryable = DefaultRetryableStrategy.handle(res);
+        let retry_forbidden = res.as_ref().is_ok_and(|response| {
+            response
+                .headers()
+                .get(Self::HEADER)
+                .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"false"))
+        });
+        match retryable {
+            Some(_) if retry_forbidden => Some(Retryable::Fatal),
+            retryable => retryable,
+        }
+    }
+}
+
+/// A [`RetryableStrategy`] giving up on responses asking to be retried too far in the future.
+///
+/// Responses the wrapped strategy would retry or reject are [`Retryable::Fatal`] when their
+/// `Retry-After` header, either in seconds or as an HTTP date, is longer than
+/// `max_acceptable_retry_after`. A `429` with `Retry-After: 3600` then fails straight away
+/// instead of retrying a request nobody is waiting for anymore. Everything else is classified by
+/// the wrapped strategy, [`DefaultRetryableStrategy`] unless told otherwise.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use reqwest_retry::{
+///     policies::ExponentialBackoff, DefaultRetryableStrategy, RetryAfterAwareFatalStrategy,
+///     RetryTransientMiddleware,
+/// };
+///
+/// let strategy =
+///     RetryAfterAwareFatalStrategy::new(DefaultRetryableStrategy, Duration::from_secs(30));
+/// let retry_policy = ExponentialBackoff::builder().build_with_max_retries(2);
+/// let middleware = RetryTransientMiddleware::new_with_policy_and_strategy(retry_policy, strategy);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RetryAfterAwareFatalStrategy<S = DefaultRetryableStrategy> {
+    inner: S,
+    max_acceptable_retry_after: Duration,
+}
+
+impl<S: RetryableStrategy> RetryAfterAwareFatalStrategy<S> {
+    /// Wrap `inner`, treating responses asking to retry after more than
+    /// `max_acceptable_retry_after` as fatal.
+    pub fn new(inner: S, max_acceptable_retry_after: Duration) -> Self {
+        Self {
+            inner,
+            max_acceptable_retry_after,
+        }
+    }
+
+    /// The longest `Retry-After` which is left to the wrapped strategy.
+    pub fn max_acceptable_retry_after(&self) -> Duration {
+        self.max_acceptable_retry_after
+    }
+}
+
+impl<S: RetryableStrategy> RetryableStrategy for RetryAfterAwareFatalStrategy<S> {
+    fn handle(&self, res: &Result<reqwest::Response, Error>) -> Option<Retryable> {
+        let retryable = self.inner.handle(res);
+        let retry_after = res.as_ref().ok().and_then(|response| {
+            let value = response.headers().get(http::header::RETRY_AFTER)?;
+            parse_retry_after(value.to_str().ok()?, SystemTime::now())
+        });
+        match retryable {
+            Some(_) if retry_after.is_some_and(|delay| delay > self.max_acceptable_retry_after) => {
+                Some(Retryable::Fatal)
+            }
+            retryable => retryable,
+        }
+    }
+}
+
+/// Parses a `Retry-After` header value, given in seconds or as an HTTP date relative to `now`.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Creates a [`RetryableStrategy`] which classifies responses by their status code.
+///
+/// Responses with a status in `transient` are retried, responses with a status in `fatal` are not,
+/// and any other response is classified by [`default_on_request_success`]. Request failures are
+/// always classified by [`default_on_request_failure`].
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use http::StatusCode;
+/// use reqwest_retry::{for_status_codes, policies::ExponentialBackoff, RetryTransientMiddleware};
+///
+/// // Retry 409s, but give up straight away on 503s.
+/// let strategy = for_status_codes(
+///     HashSet::from([StatusCode::CONFLICT]),
+///     HashSet::from([StatusCode::SERVICE_UNAVAILABLE]),
+/// );
+/// let retry_policy = ExponentialBackoff::builder().build_with_max_retries(2);
+/// let middleware = RetryTransientMiddleware::new_with_policy_and_strategy(retry_policy, strategy);
+/// ```
+pub fn for_status_codes(
+    transient: HashSet<StatusCode>,
+    fatal: HashSet<StatusCode>,
+) -> impl RetryableStrategy {
+    StatusCodeRetryableStrategy { transient, fatal }
+}
+
+struct StatusCodeRetryableStrategy {
+    transient: HashSet<StatusCode>,
+    fatal: HashSet<StatusCode>,
+}
+
+impl RetryableStrategy for StatusCodeRetryableStrategy {
+    fn handle(&self, res: &Result<reqwest::Response, Error>) -> Option<Retryable> {
+        match res {
+            Ok(success) if self.transient.contains(&success.status()) => Some(Retryable::Transient),
+            Ok(success) if self.fatal.contains(&success.status()) => Some(Retryable::Fatal),
+            Ok(success) => default_on_request_success(success),
+            Err(error) => default_on_request_failure(error),
+        }
+    }
+}
+
 /// Default request success retry strategy.
 ///
 /// Will only retry if:
@@ -111,7 +268,11 @@ impl RetryableStrategy for DefaultRetryableStrategy {
 ///
 /// Note that success here means that the request finished without interruption, not that it was logically OK.
 pub fn default_on_request_success(success: &reqwest::Response) -> Option<Retryable> {
-    let status = success.status();
+    classify_status(success.status())
+}
+
+/// Classifies a response status, see [`default_on_request_success`].
+fn classify_status(status: StatusCode) -> Option<Retryable> {
     if status.is_server_error() {
         Some(Retryable::Transient)
     } else if status.is_client_error()
@@ -130,10 +291,18 @@ pub fn default_on_request_success(success: &reqwest::Response) -> Option<Retryab
 
 /// Default request failure retry strategy.
 ///
-/// Will only retry if the request failed due to a network error
+/// Will only retry if the request failed due to a network error, or if the error carries a
+/// status that [`default_on_request_success`] would retry, e.g. when downstream middleware called
+/// [`Response::error_for_status`](reqwest::Response::error_for_status). Middleware errors are only
+/// retried if they were created from a [`MiddlewareError`] which [is
+/// retryable](reqwest_middleware::MiddlewareError::is_retryable).
+///
+/// [`MiddlewareError`]: reqwest_middleware::MiddlewareError
 pub fn default_on_request_failure(error: &Error) -> Option<Retryable> {
     match error {
-        // If something fails in the middleware we're screwed.
+        // Middleware can flag their errors as transient with `MiddlewareError::is_retryable`,
+        // otherwise if something fails in the middleware we're screwed.
+        Error::Middleware(_) if error.is_retryable_middleware_error() => Some(Retryable::Transient),
         Error::Middleware(_) => Some(Retryable::Fatal),
         Error::Reqwest(error) => {
             #[cfg(not(target_arch = "wasm32"))]
@@ -149,54 +318,81 @@ pub fn default_on_request_failure(error: &Error) -> Option<Retryable> {
             {
                 Some(Retryable::Fatal)
             } else if error.is_request() {
-                // It seems that hyper::Error(IncompleteMessage) is not correctly handled by reqwest.
-                // Here we check if the Reqwest error was originated by hyper and map it consistently.
-                #[cfg(not(target_arch = "wasm32"))]
-                if let Some(hyper_error) = get_source_error_type::<hyper::Error>(&error) {
-                    // The hyper::Error(IncompleteMessage) is raised if the HTTP response is well formatted but does not contain all the bytes.
-                    // This can happen when the server has started sending back the response but the connection is cut halfway through.
-                    // We can safely retry the call, hence marking this error as [`Retryable::Transient`].
-                    // Instead hyper::Error(Canceled) is raised when the connection is
-                    // gracefully closed on the server side.
-                    if hyper_error.is_incomplete_message() || hyper_error.is_canceled() {
-                        Some(Retryable::Transient)
-
-                    // Try and downcast the hyper error to io::Error if that is the
-                    // underlying error, and try and classify it.
-                    } else if let Some(io_error) =
-                        get_source_error_type::<std::io::Error>(hyper_error)
-                    {
-                        Some(classify_io_error(io_error))
-                    } else {
-                        Some(Retryable::Fatal)
-                    }
-                } else {
-                    Some(Retryable::Fatal)
-                }
-                #[cfg(target_arch = "wasm32")]
-                Some(Retryable::Fatal)
+                Some(classify_request_error(error))
+            } else if let Some(status) = error.status().or_else(|| source_status(error)) {
+                // Downstream middleware may turn responses into errors with
+                // `Response::error_for_status`, classify them like the responses themselves.
+                classify_status(status)
             } else {
-                // We omit checking if error.is_status() since we check that already.
-                // However, if Response::error_for_status is used the status will still
-                // remain in the response object.
                 None
             }
         }
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn classify_io_error(error: &std::io::Error) -> Retryable {
-    match error.kind() {
-        std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::ConnectionAborted => {
-            Retryable::Transient
+/// The kinds of IO errors classified as [`Retryable::Transient`] by [`classify_io_error`].
+///
+/// Besides connections that were reset or aborted, refused connections are retried as the server
+/// may be starting or restarting, e.g. a sidecar proxy racing the application at startup.
+pub const TRANSIENT_IO_ERROR_KINDS: &[std::io::ErrorKind] = &[
+    std::io::ErrorKind::ConnectionReset,
+    std::io::ErrorKind::ConnectionAborted,
+    std::io::ErrorKind::ConnectionRefused,
+];
+
+/// Classifies an IO error, as done by [`default_on_request_failure`] for the IO errors causing
+/// `reqwest` errors.
+///
+/// IO errors of one of the [`TRANSIENT_IO_ERROR_KINDS`] are [`Retryable::Transient`], any other
+/// IO error is [`Retryable::Fatal`].
+pub fn classify_io_error(error: &std::io::Error) -> Retryable {
+    if TRANSIENT_IO_ERROR_KINDS.contains(&error.kind()) {
+        Retryable::Transient
+    } else {
+        Retryable::Fatal
+    }
+}
+
+/// Classifies a `reqwest` request error from its source chain.
+fn classify_request_error(error: &(dyn std::error::Error + 'static)) -> Retryable {
+    // It seems that hyper::Error(IncompleteMessage) is not correctly handled by reqwest.
+    // Here we check if the Reqwest error was originated by hyper and map it consistently.
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(hyper_error) = get_source_error_type::<hyper::Error>(error) {
+        // The hyper::Error(IncompleteMessage) is raised if the HTTP response is well formatted but does not contain all the bytes.
+        // This can happen when the server has started sending back the response but the connection is cut halfway through.
+        // We can safely retry the call, hence marking this error as [`Retryable::Transient`].
+        // Instead hyper::Error(Canceled) is raised when the connection is
+        // gracefully closed on the server side.
+        if hyper_error.is_incomplete_message() || hyper_error.is_canceled() {
+            return Retryable::Transient;
         }
-        _ => Retryable::Fatal,
+    }
+
+    // Look for an underlying io::Error anywhere in the chain, whether it comes from hyper or not,
+    // and try and classify it.
+    match get_source_error_type::<std::io::Error>(error) {
+        Some(io_error) => classify_io_error(io_error),
+        None => Retryable::Fatal,
     }
 }
 
+/// Looks for the status of a `reqwest` error wrapped by the given error, e.g. by a body stream.
+fn source_status(err: &reqwest::Error) -> Option<StatusCode> {
+    let mut source = std::error::Error::source(err);
+    while let Some(err) = source {
+        if let Some(status) = err
+            .downcast_ref::<reqwest::Error>()
+            .and_then(reqwest::Error::status)
+        {
+            return Some(status);
+        }
+        source = err.source();
+    }
+    None
+}
+
 /// Downcasts the given err source into T.
-#[cfg(not(target_arch = "wasm32"))]
 fn get_source_error_type<T: std::error::Error + 'static>(
     err: &dyn std::error::Error,
 ) -> Option<&T> {
@@ -211,3 +407,65 @@ fn get_source_error_type<T: std::error::Error + 'static>(
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    /// An error wrapping an IO error, like a non-hyper transport would.
+    #[derive(Debug, thiserror::Error)]
+    #[error("transport error")]
+    struct TransportError(#[source] std::io::Error);
+
+    /// Request errors are reported with the transport error as their source.
+    #[derive(Debug, thiserror::Error)]
+    #[error("error sending request")]
+    struct RequestError(#[source] TransportError);
+
+    #[test]
+    fn io_errors_are_classified_without_hyper() {
+        for &(kind, expected) in &[
+            (ErrorKind::ConnectionReset, Retryable::Transient),
+            (ErrorKind::ConnectionAborted, Retryable::Transient),
+            (ErrorKind::PermissionDenied, Retryable::Fatal),
+        ] {
+            let error = RequestError(TransportError(std::io::Error::from(kind)));
+            assert_eq!(classify_request_error(&error), expected, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn refused_connections_are_transient_outside_of_connect_errors() {
+        // `reqwest` reports refused connections as connect errors, which are retried anyway. Other
+        // request errors caused by a refused connection, e.g. from a custom connector, are retried
+        // because of their IO error.
+        let error = RequestError(TransportError(std::io::Error::from(
+            ErrorKind::ConnectionRefused,
+        )));
+        assert_eq!(classify_request_error(&error), Retryable::Transient);
+    }
+
+    #[test]
+    fn retry_after_is_parsed_from_seconds_and_dates() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        for &(value, expected) in &[
+            ("3600", Some(Duration::from_secs(3600))),
+            (" 5 ", Some(Duration::from_secs(5))),
+            (
+                "Wed, 21 Oct 2015 08:28:00 GMT",
+                Some(Duration::from_secs(3600)),
+            ),
+            ("Wed, 21 Oct 2015 06:28:00 GMT", Some(Duration::ZERO)),
+            ("-1", None),
+            ("soon", None),
+        ] {
+            assert_eq!(parse_retry_after(value, now), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn request_errors_without_io_error_are_fatal() {
+        assert_eq!(classify_request_error(&std::fmt::Error), Retryable::Fatal);
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use reqwest_retry::{policies::ExponentialBackoff, BlockingRetryTransientMiddleware, RetryError};
+
+/// Serves `statuses` in order, one per connection, on a plain thread.
+///
+/// Returns the server url and the number of requests received.
+fn serve(statuses: Vec<u16>) -> (String, Arc<AtomicU32>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/foo", listener.local_addr().unwrap());
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    thread::spawn(move || {
+        for (stream, status) in listener.incoming().zip(statuses) {
+            let mut stream = stream.unwrap();
+            // Read the request head, there is no body.
+            let mut reader = BufReader::new(&mut stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            counter.fetch_add(1, Ordering::SeqCst);
+            write!(
+                stream,
+                "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+        }
+    });
+    (url, calls)
+}
+
+fn client(max_retries: u32) -> BlockingRetryTransientMiddleware<ExponentialBackoff> {
+    let delay = Duration::from_millis(10);
+    BlockingRetryTransientMiddleware::new_with_policy(
+        reqwest::blocking::Client::new(),
+        ExponentialBackoff::builder()
+            .retry_bounds(delay, delay)
+            .jitter(reqwest_retry::Jitter::None)
+            .build_with_max_retries(max_retries),
+    )
+}
+
+#[test]
+fn blocking_retries_until_success() {
+    let (url, calls) = serve(vec![500, 503, 200]);
+    let client = client(3);
+
+    let request = client.client().get(url).build().unwrap();
+    let response = client.execute(request).unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn blocking_returns_the_last_response_when_retries_are_exhausted() {
+    let (url, calls) = serve(vec![500; 3]);
+    let client = client(2);
+
+    let request = client.client().get(url).build().unwrap();
+    let response = client.execute(request).unwrap();
+
+    assert_eq!(response.status(), 500);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn blocking_does_not_retry_fatal_responses() {
+    let (url, calls) = serve(vec![400, 200]);
+    let client = client(3);
+
+    let request = client.client().get(url).build().unwrap();
+    let response = client.execute(request).unwrap();
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn blocking_reports_exhausted_retries_on_connection_errors() {
+    // Nothing listens on the port once the listener is dropped.
+    let url = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/foo", listener.local_addr().unwrap())
+    };
+    let client = client(1);
+
+    let request = client.client().get(url).build().unwrap();
+    let err = client.execute(request).unwrap_err();
+
+    match err {
+        reqwest_middleware::Error::Middleware(err) => {
+            match *err.downcast::<RetryError>().unwrap() {
+                RetryError::RetriesExhausted { attempts, .. } => assert_eq!(attempts, 2),
+                err => panic!("unexpected error: {:?}", err),
+            }
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
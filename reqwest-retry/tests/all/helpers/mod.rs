@@ -1,3 +1,27 @@
 mod simple_server;
 
+use reqwest_retry::policies::ExponentialBackoff;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Respond};
+
 pub use simple_server::SimpleServer;
+
+/// A retry policy allowing `max_retries` retries, barely waiting between them.
+pub fn fast_backoff(max_retries: u32) -> ExponentialBackoff {
+    ExponentialBackoff::builder()
+        .retry_bounds(Duration::from_millis(1), Duration::from_millis(1))
+        .build_with_max_retries(max_retries)
+}
+
+/// Starts a server answering `GET /foo` with `responder`, expecting `expected_calls` requests.
+pub async fn mock_get_foo(responder: impl Respond + 'static, expected_calls: u64) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/foo"))
+        .respond_with(responder)
+        .expect(expected_calls)
+        .mount(&server)
+        .await;
+    server
+}
@@ -1,2 +1,7 @@
+// The tests running on `wasm32` are in `tests/wasm`, these need a server and the tokio runtime.
+#![cfg(not(target_arch = "wasm32"))]
+
+#[cfg(feature = "blocking")]
+mod blocking;
 mod helpers;
 mod retry;
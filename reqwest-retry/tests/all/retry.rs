@@ -2,8 +2,15 @@ use futures::FutureExt;
 use paste::paste;
 use reqwest::Client;
 use reqwest::StatusCode;
-use reqwest_middleware::ClientBuilder;
-use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use reqwest_middleware::{ClientBuilder, ResendCount};
+use reqwest_retry::{
+    for_status_codes, policies::ExponentialBackoff, Counter, DefaultRetryableStrategy,
+    ExponentialBackoffBuilderExt, HeaderControlledRetryStrategy, MethodRetryConfig,
+    RequestBuilderRetryExt, RequestId, RetryAfterAwareFatalStrategy, RetryAttemptRecord,
+    RetryDecisionOverride, RetryEvent, RetryFailureKind, RetryFinalResult, RetryLabels,
+    RetryMetricOutcome, RetryOutcome, RetryTransientMiddleware, RetryUrlTemplate, Retryable,
+};
+use std::collections::HashSet;
 use std::sync::atomic::AtomicI8;
 use std::sync::{
     atomic::{AtomicU32, Ordering},
@@ -14,7 +21,7 @@ use tokio::io::AsyncWriteExt;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, Respond, ResponseTemplate};
 
-use crate::helpers::SimpleServer;
+use crate::helpers::{fast_backoff, mock_get_foo, SimpleServer};
 pub struct RetryResponder(Arc<AtomicU32>, u32, u16);
 
 impl RetryResponder {
@@ -175,16 +182,11 @@ impl Respond for RetryTimeoutResponder {
 
 #[tokio::test]
 async fn assert_retry_on_request_timeout() {
-    let server = MockServer::start().await;
-    Mock::given(method("GET"))
-        .and(path("/foo"))
-        .respond_with(RetryTimeoutResponder::new(
-            3,
-            std::time::Duration::from_millis(1000),
-        ))
-        .expect(2)
-        .mount(&server)
-        .await;
+    let server = mock_get_foo(
+        RetryTimeoutResponder::new(3, std::time::Duration::from_millis(1000)),
+        2,
+    )
+    .await;
 
     let reqwest_client = Client::builder().build().unwrap();
     let client = ClientBuilder::new(reqwest_client)
@@ -361,3 +363,1350 @@ async fn assert_retry_on_connection_reset_by_peer() {
 
     assert_eq!(resp.status(), 200);
 }
+
+struct ResendCountRecorder(Arc<std::sync::Mutex<Vec<u32>>>);
+
+#[async_trait::async_trait]
+impl reqwest_middleware::Middleware for ResendCountRecorder {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        extensions: &mut http::Extensions,
+        next: reqwest_middleware::Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        let ResendCount(resend_count) = extensions.get::<ResendCount>().copied().unwrap();
+        self.0.lock().unwrap().push(resend_count);
+        next.run(req, extensions).await
+    }
+}
+
+#[tokio::test]
+async fn assert_resend_count_is_set_on_each_attempt() {
+    let server = mock_get_foo(RetryResponder::new(3, 500), 2).await;
+
+    let resend_counts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(30),
+                    std::time::Duration::from_millis(100),
+                )
+                .build_with_max_retries(3),
+        ))
+        .with(ResendCountRecorder(resend_counts.clone()))
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(*resend_counts.lock().unwrap(), vec![0, 1]);
+}
+
+#[tokio::test]
+async fn assert_retry_decision_override_disables_retries() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 1).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(30),
+                    std::time::Duration::from_millis(100),
+                )
+                .build_with_max_retries(3),
+        ))
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .with_extension(RetryDecisionOverride(None))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 500);
+}
+
+#[tokio::test]
+async fn assert_retry_decision_override_forces_retries() {
+    let server = mock_get_foo(ResponseTemplate::new(200), 3).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(30),
+                    std::time::Duration::from_millis(100),
+                )
+                .build_with_max_retries(2),
+        ))
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .with_extension(RetryDecisionOverride(Some(Retryable::Transient)))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn assert_method_config_overrides_retry_policy() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 4).await;
+    Mock::given(method("POST"))
+        .and(path("/foo"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let backoff = || {
+        ExponentialBackoff::builder().retry_bounds(
+            std::time::Duration::from_millis(30),
+            std::time::Duration::from_millis(100),
+        )
+    };
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::new_with_policy(backoff().build_with_max_retries(3))
+                .with_method_config(
+                    MethodRetryConfig::new()
+                        .with_policy(reqwest::Method::POST, backoff().build_with_max_retries(1)),
+                ),
+        )
+        .build();
+
+    let url = format!("{}/foo", server.uri());
+    let resp = client.get(&url).send().await.expect("call failed");
+    assert_eq!(resp.status(), 500);
+    let resp = client.post(&url).send().await.expect("call failed");
+    assert_eq!(resp.status(), 500);
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("circuit breaker is open")]
+struct CircuitOpen;
+
+struct FlakyCircuitBreaker(AtomicU32);
+
+#[async_trait::async_trait]
+impl reqwest_middleware::Middleware for FlakyCircuitBreaker {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        extensions: &mut http::Extensions,
+        next: reqwest_middleware::Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        if self.0.fetch_add(1, Ordering::SeqCst) < 2 {
+            return Err(reqwest_middleware::Error::middleware(CircuitOpen));
+        }
+        next.run(req, extensions).await
+    }
+}
+
+#[tokio::test]
+async fn assert_middleware_error_strategy_classifies_middleware_errors() {
+    let server = mock_get_foo(ResponseTemplate::new(200), 1).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::new_with_policy(
+                ExponentialBackoff::builder()
+                    .retry_bounds(
+                        std::time::Duration::from_millis(30),
+                        std::time::Duration::from_millis(100),
+                    )
+                    .build_with_max_retries(3),
+            )
+            .with_middleware_error_strategy(Arc::new(|err| {
+                if err.is::<CircuitOpen>() {
+                    Some(Retryable::Transient)
+                } else {
+                    Some(Retryable::Fatal)
+                }
+            })),
+        )
+        .with(FlakyCircuitBreaker(AtomicU32::new(0)))
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 200);
+}
+
+async fn send_through_open_circuit(
+    circuit_open: Retryable,
+    max_retries: u32,
+) -> reqwest_retry::RetryError {
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::new_with_policy(
+                ExponentialBackoff::builder()
+                    .retry_bounds(
+                        std::time::Duration::from_millis(30),
+                        std::time::Duration::from_millis(30),
+                    )
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(max_retries),
+            )
+            .with_middleware_error_strategy(Arc::new(move |_| Some(circuit_open))),
+        )
+        .with(FlakyCircuitBreaker(AtomicU32::new(0)))
+        .build();
+
+    let err = client
+        .get("http://localhost/foo")
+        .send()
+        .await
+        .expect_err("call succeeded");
+    match err {
+        reqwest_middleware::Error::Middleware(err) => *err
+            .downcast::<reqwest_retry::RetryError>()
+            .expect("not a retry error"),
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn assert_retries_exhausted_error_after_transient_failures() {
+    match send_through_open_circuit(Retryable::Transient, 1).await {
+        reqwest_retry::RetryError::RetriesExhausted {
+            attempts,
+            last_error,
+            total_elapsed,
+        } => {
+            assert_eq!(attempts, 2);
+            assert!(total_elapsed >= std::time::Duration::from_millis(30));
+            match *last_error {
+                reqwest_middleware::Error::Middleware(err) => assert!(err.is::<CircuitOpen>()),
+                err => panic!("unexpected error: {:?}", err),
+            }
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn assert_no_retries_exhausted_error_without_retries() {
+    match send_through_open_circuit(Retryable::Transient, 0).await {
+        reqwest_retry::RetryError::Error(reqwest_middleware::Error::Middleware(err)) => {
+            assert!(err.is::<CircuitOpen>())
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn assert_no_retries_exhausted_error_after_fatal_failure() {
+    match send_through_open_circuit(Retryable::Fatal, 1).await {
+        reqwest_retry::RetryError::Error(reqwest_middleware::Error::Middleware(err)) => {
+            assert!(err.is::<CircuitOpen>())
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+struct RequestIdRecorder(Arc<std::sync::Mutex<Vec<RequestId>>>);
+
+#[async_trait::async_trait]
+impl reqwest_middleware::Middleware for RequestIdRecorder {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        extensions: &mut http::Extensions,
+        next: reqwest_middleware::Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        let request_id = extensions.get::<RequestId>().copied().unwrap();
+        self.0.lock().unwrap().push(request_id);
+        next.run(req, extensions).await
+    }
+}
+
+#[tokio::test]
+async fn assert_request_id_is_stable_across_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/foo"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let request_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(30),
+                    std::time::Duration::from_millis(100),
+                )
+                .build_with_max_retries(2),
+        ))
+        .with(RequestIdRecorder(request_ids.clone()))
+        .build();
+
+    let url = format!("{}/foo", server.uri());
+    client.get(&url).send().await.expect("call failed");
+    client.get(&url).send().await.expect("call failed");
+
+    let request_ids = request_ids.lock().unwrap();
+    assert_eq!(request_ids.len(), 6);
+    assert!(request_ids[..3].iter().all(|id| *id == request_ids[0]));
+    assert!(request_ids[3..].iter().all(|id| *id == request_ids[3]));
+    assert_ne!(request_ids[0], request_ids[3]);
+}
+
+#[tokio::test]
+async fn assert_retry_on_connection_refused() {
+    // `reqwest` reports the refused connections as connect errors.
+    // Reserve a free port, nothing listens on it until the server below starts.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let uri = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let simple_server = SimpleServer::new(
+            "127.0.0.1",
+            Some(port),
+            vec!["HTTP/1.1 200 OK\r\n\r\n".to_string()],
+        )
+        .await
+        .expect("Error when creating a simple server");
+        simple_server.start().await;
+    });
+
+    let attempts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(50),
+                    std::time::Duration::from_millis(200),
+                )
+                .build_with_max_retries(5),
+        ))
+        .with(RequestIdRecorder(attempts.clone()))
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", uri))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 200);
+    assert!(attempts.lock().unwrap().len() > 1);
+}
+
+#[tokio::test]
+async fn assert_builder_hooks_are_called_on_retry() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/foo"))
+        .and(wiremock::matchers::header_exists("x-retry"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/foo"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::<RetryEvent>::new()));
+    let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded_events = events.clone();
+    let recorded_sleeps = sleeps.clone();
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(
+                        std::time::Duration::from_secs(30),
+                        std::time::Duration::from_secs(60),
+                    )
+                    .build_with_max_retries(3),
+            )
+            .on_retry(Arc::new(move |event| {
+                recorded_events.lock().unwrap().push(event)
+            }))
+            .sleep_fn(Arc::new(move |duration| {
+                recorded_sleeps.lock().unwrap().push(duration);
+                Box::pin(async {})
+            }))
+            .pre_retry_hook(Arc::new(|req, _| {
+                req.headers_mut()
+                    .insert("x-retry", http::HeaderValue::from_static("true"));
+            }))
+            .build(),
+        )
+        .build();
+
+    let url = format!("{}/foo", server.uri());
+    let resp = client.get(&url).send().await.expect("call failed");
+
+    assert_eq!(resp.status(), 200);
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].method, reqwest::Method::GET);
+    assert_eq!(events[0].url.as_str(), url);
+    assert_eq!(*sleeps.lock().unwrap(), [events[0].delay]);
+}
+
+#[tokio::test]
+async fn assert_retry_events_report_the_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(4)
+        .mount(&server)
+        .await;
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded_events = events.clone();
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder().build_with_max_retries(3),
+            )
+            .on_retry(Arc::new(move |event| {
+                recorded_events.lock().unwrap().push((
+                    event.is_first_attempt(),
+                    event.is_last_attempt(),
+                    event.total_attempts_so_far(),
+                ))
+            }))
+            .sleep_fn(Arc::new(|_| Box::pin(async {})))
+            .build(),
+        )
+        .build();
+
+    let resp = client.get(server.uri()).send().await.expect("call failed");
+
+    assert_eq!(resp.status(), 500);
+    assert_eq!(
+        *events.lock().unwrap(),
+        [(true, false, 1), (false, false, 2), (false, true, 3)]
+    );
+}
+
+#[tokio::test]
+async fn assert_builder_max_elapsed_stops_retries() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 1).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(
+                        std::time::Duration::from_millis(100),
+                        std::time::Duration::from_millis(100),
+                    )
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(10),
+            )
+            .max_elapsed(std::time::Duration::from_millis(50))
+            .build(),
+        )
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 500);
+}
+
+#[tokio::test]
+async fn assert_builder_total_timeout_stops_retries() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 2).await;
+
+    // The policy allows 10 retries, the total timeout only leaves room for the first one.
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(
+                        std::time::Duration::from_millis(100),
+                        std::time::Duration::from_millis(100),
+                    )
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(10),
+            )
+            .with_total_timeout(std::time::Duration::from_millis(150))
+            .build(),
+        )
+        .build();
+
+    let start = std::time::Instant::now();
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 500);
+    assert!(start.elapsed() < std::time::Duration::from_millis(150));
+}
+
+#[tokio::test]
+async fn assert_max_elapsed_accounts_for_the_upcoming_delay() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 2).await;
+
+    // The first retry ends 200ms after the first attempt, the second one would end after 400ms.
+    let sleeps = Arc::new(AtomicU32::new(0));
+    let counted_sleeps = sleeps.clone();
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(
+                        std::time::Duration::from_millis(200),
+                        std::time::Duration::from_millis(200),
+                    )
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(10),
+            )
+            .sleep_fn(Arc::new(move |duration| {
+                counted_sleeps.fetch_add(1, Ordering::SeqCst);
+                Box::pin(tokio::time::sleep(duration))
+            }))
+            .build()
+            .with_max_elapsed(std::time::Duration::from_millis(300)),
+        )
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 500);
+    assert_eq!(sleeps.load(Ordering::SeqCst), 1);
+}
+
+async fn send_with_status_code_strategy(status: u16, expected_calls: u64) -> StatusCode {
+    let server = mock_get_foo(ResponseTemplate::new(status), expected_calls).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+            fast_backoff(2),
+            for_status_codes(
+                HashSet::from([StatusCode::CONFLICT]),
+                HashSet::from([StatusCode::SERVICE_UNAVAILABLE]),
+            ),
+        ))
+        .build();
+
+    client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed")
+        .status()
+}
+
+#[tokio::test]
+async fn assert_status_code_strategy_retries_transient_codes() {
+    assert_eq!(send_with_status_code_strategy(409, 3).await, 409);
+}
+
+#[tokio::test]
+async fn assert_status_code_strategy_does_not_retry_fatal_codes() {
+    assert_eq!(send_with_status_code_strategy(503, 1).await, 503);
+}
+
+#[tokio::test]
+async fn assert_status_code_strategy_falls_back_to_default() {
+    assert_eq!(send_with_status_code_strategy(500, 3).await, 500);
+    assert_eq!(send_with_status_code_strategy(404, 1).await, 404);
+}
+
+async fn send_with_header_controlled_strategy(
+    response: ResponseTemplate,
+    expected_calls: u64,
+) -> StatusCode {
+    let server = mock_get_foo(response, expected_calls).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+            fast_backoff(2),
+            HeaderControlledRetryStrategy,
+        ))
+        .build();
+
+    client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed")
+        .status()
+}
+
+#[tokio::test]
+async fn assert_header_controlled_strategy_does_not_retry_when_forbidden() {
+    let response = ResponseTemplate::new(503).insert_header("X-Retry-Allowed", "false");
+    assert_eq!(send_with_header_controlled_strategy(response, 1).await, 503);
+}
+
+#[tokio::test]
+async fn assert_header_controlled_strategy_retries_without_the_header() {
+    let response = ResponseTemplate::new(503);
+    assert_eq!(send_with_header_controlled_strategy(response, 3).await, 503);
+    let response = ResponseTemplate::new(503).insert_header("X-Retry-Allowed", "true");
+    assert_eq!(send_with_header_controlled_strategy(response, 3).await, 503);
+}
+
+#[tokio::test]
+async fn assert_header_controlled_strategy_keeps_successful_responses() {
+    let response = ResponseTemplate::new(200).insert_header("X-Retry-Allowed", "false");
+    assert_eq!(send_with_header_controlled_strategy(response, 1).await, 200);
+}
+
+async fn send_with_retry_after_aware_strategy(
+    response: ResponseTemplate,
+    expected_calls: u64,
+) -> StatusCode {
+    let server = mock_get_foo(response, expected_calls).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+            fast_backoff(2),
+            RetryAfterAwareFatalStrategy::new(
+                DefaultRetryableStrategy,
+                std::time::Duration::from_secs(30),
+            ),
+        ))
+        .build();
+
+    client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed")
+        .status()
+}
+
+#[tokio::test]
+async fn assert_retry_after_aware_strategy_gives_up_on_long_retry_after() {
+    let response = ResponseTemplate::new(429).insert_header("Retry-After", "3600");
+    assert_eq!(send_with_retry_after_aware_strategy(response, 1).await, 429);
+}
+
+#[tokio::test]
+async fn assert_retry_after_aware_strategy_retries_short_retry_after() {
+    let response = ResponseTemplate::new(429).insert_header("Retry-After", "1");
+    assert_eq!(send_with_retry_after_aware_strategy(response, 3).await, 429);
+    let response = ResponseTemplate::new(429);
+    assert_eq!(send_with_retry_after_aware_strategy(response, 3).await, 429);
+}
+
+#[cfg(feature = "jitter")]
+#[tokio::test]
+async fn assert_jitter_randomises_the_delay() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 6).await;
+
+    let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded_sleeps = sleeps.clone();
+    let delay = std::time::Duration::from_secs(1);
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(delay, delay)
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(5),
+            )
+            .sleep_fn(Arc::new(move |duration| {
+                recorded_sleeps.lock().unwrap().push(duration);
+                Box::pin(async {})
+            }))
+            .build()
+            .with_jitter(0.5),
+        )
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+    assert_eq!(resp.status(), 500);
+
+    let sleeps = sleeps.lock().unwrap();
+    assert_eq!(sleeps.len(), 5);
+    assert!(sleeps.iter().all(|sleep| *sleep <= delay.mul_f64(1.5)));
+    let spread = *sleeps.iter().max().unwrap() - *sleeps.iter().min().unwrap();
+    assert!(
+        spread > std::time::Duration::from_millis(10),
+        "delays should vary: {:?}",
+        sleeps
+    );
+}
+
+#[cfg(feature = "jitter")]
+#[tokio::test]
+async fn assert_nan_jitter_is_disabled() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 3).await;
+
+    let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded_sleeps = sleeps.clone();
+    let delay = std::time::Duration::from_secs(1);
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(delay, delay)
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(2),
+            )
+            .jitter(f64::NAN)
+            .sleep_fn(Arc::new(move |duration| {
+                recorded_sleeps.lock().unwrap().push(duration);
+                Box::pin(async {})
+            }))
+            .build()
+            .with_jitter(f64::NAN),
+        )
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+    assert_eq!(resp.status(), 500);
+    // The delays are only shortened by the time spent on the attempts.
+    let sleeps = sleeps.lock().unwrap();
+    assert_eq!(sleeps.len(), 2);
+    assert!(
+        sleeps
+            .iter()
+            .all(|sleep| *sleep <= delay && *sleep > delay.mul_f64(0.9)),
+        "{:?}",
+        sleeps
+    );
+}
+
+#[cfg(feature = "jitter")]
+async fn jittered_sleeps(seed: u64) -> Vec<std::time::Duration> {
+    let server = mock_get_foo(ResponseTemplate::new(500), 4).await;
+
+    let sleeps = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded_sleeps = sleeps.clone();
+    let delay = std::time::Duration::from_secs(1);
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::builder(
+                ExponentialBackoff::builder()
+                    .retry_bounds(delay, delay)
+                    .jitter(reqwest_retry::Jitter::None)
+                    .build_with_max_retries(3),
+            )
+            .sleep_fn(Arc::new(move |duration| {
+                recorded_sleeps.lock().unwrap().push(duration);
+                Box::pin(async {})
+            }))
+            .jitter(0.5)
+            .jitter_seed(seed)
+            .build(),
+        )
+        .build();
+
+    client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    let sleeps = sleeps.lock().unwrap().clone();
+    sleeps
+}
+
+#[cfg(feature = "jitter")]
+#[tokio::test]
+async fn assert_jitter_seed_makes_the_delay_deterministic() {
+    // The delay of the policy itself is only accurate to the time spent between attempts.
+    let close = |a: Vec<std::time::Duration>, b: &[std::time::Duration]| {
+        a.iter().zip(b).all(|(a, b)| {
+            let diff = if a > b { *a - *b } else { *b - *a };
+            diff < std::time::Duration::from_millis(5)
+        })
+    };
+    let sleeps = jittered_sleeps(42).await;
+
+    assert_eq!(sleeps.len(), 3);
+    assert!(close(jittered_sleeps(42).await, &sleeps));
+    assert!(!close(jittered_sleeps(7).await, &sleeps));
+}
+
+#[tokio::test]
+async fn assert_send_retry_only_retries_that_request() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 4).await;
+
+    let client = ClientBuilder::new(Client::new()).build();
+    let url = format!("{}/foo", server.uri());
+
+    let resp = client
+        .get(&url)
+        .send_retry(fast_backoff(2))
+        .await
+        .expect("call failed");
+    assert_eq!(resp.status(), 500);
+
+    // Without `send_retry`, the client doesn't retry.
+    let resp = client.get(&url).send().await.expect("call failed");
+    assert_eq!(resp.status(), 500);
+}
+
+#[tokio::test]
+async fn assert_policy_fn_retries_twice() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 3).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy_fn(
+            |_: &std::time::SystemTime, n_past_retries| {
+                if n_past_retries < 2 {
+                    reqwest_retry::RetryDecision::Retry {
+                        execute_after: std::time::SystemTime::now()
+                            + std::time::Duration::from_millis(10),
+                    }
+                } else {
+                    reqwest_retry::RetryDecision::DoNotRetry
+                }
+            },
+        ))
+        .build();
+
+    let resp = client
+        .get(format!("{}/foo", server.uri()))
+        .send()
+        .await
+        .expect("call failed");
+
+    assert_eq!(resp.status(), 500);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Attempts(u32);
+
+struct AttemptCounter;
+
+#[async_trait::async_trait]
+impl reqwest_middleware::Middleware for AttemptCounter {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        extensions: &mut http::Extensions,
+        next: reqwest_middleware::Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        extensions.get_or_insert(Attempts(0)).0 += 1;
+        next.run(req, extensions).await
+    }
+}
+
+async fn count_attempts(isolate_extensions: bool) -> Attempts {
+    let server = mock_get_foo(ResponseTemplate::new(500), 3).await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::new_with_policy(fast_backoff(2))
+                .with_extensions_isolation_on_retry(isolate_extensions),
+        )
+        .with(AttemptCounter)
+        .build();
+
+    let req = client.get(format!("{}/foo", server.uri())).build().unwrap();
+    let mut extensions = http::Extensions::new();
+    let resp = client
+        .execute_with_extensions(req, &mut extensions)
+        .await
+        .expect("call failed");
+    assert_eq!(resp.status(), 500);
+    *extensions.get::<Attempts>().unwrap()
+}
+
+#[tokio::test]
+async fn assert_extensions_are_shared_between_attempts_by_default() {
+    assert_eq!(count_attempts(false).await, Attempts(3));
+}
+
+#[tokio::test]
+async fn assert_extensions_isolation_discards_retried_attempts() {
+    assert_eq!(count_attempts(true).await, Attempts(1));
+}
+
+async fn send_and_get_outcome(server: &MockServer) -> RetryOutcome {
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(30),
+                    std::time::Duration::from_millis(30),
+                )
+                .jitter(reqwest_retry::Jitter::None)
+                .build_with_max_retries(2),
+        ))
+        .build();
+
+    let req = client.get(format!("{}/foo", server.uri())).build().unwrap();
+    let mut extensions = http::Extensions::new();
+    client
+        .execute_with_extensions(req, &mut extensions)
+        .await
+        .expect("call failed");
+    *extensions.get::<RetryOutcome>().unwrap()
+}
+
+#[tokio::test]
+async fn assert_retry_outcome_after_success_on_retry() {
+    let server = mock_get_foo(RetryResponder::new(3, 500), 2).await;
+
+    let outcome = send_and_get_outcome(&server).await;
+
+    assert_eq!(outcome.attempts, 2);
+    assert_eq!(outcome.final_result, RetryFinalResult::Success);
+    assert!(outcome.total_elapsed >= std::time::Duration::from_millis(30));
+}
+
+#[tokio::test]
+async fn assert_retry_outcome_after_exhausted_retries() {
+    let server = mock_get_foo(ResponseTemplate::new(500), 3).await;
+
+    let outcome = send_and_get_outcome(&server).await;
+
+    assert_eq!(outcome.attempts, 3);
+    assert_eq!(
+        outcome.final_result,
+        RetryFinalResult::Failed(RetryFailureKind::RetriesExhausted)
+    );
+    assert!(outcome.total_elapsed >= std::time::Duration::from_millis(60));
+}
+
+#[tokio::test]
+async fn assert_retry_outcome_after_fatal_error() {
+    let server = mock_get_foo(ResponseTemplate::new(400), 1).await;
+
+    let outcome = send_and_get_outcome(&server).await;
+
+    assert_eq!(outcome.attempts, 1);
+    assert_eq!(
+        outcome.final_result,
+        RetryFinalResult::Failed(RetryFailureKind::Fatal)
+    );
+}
+
+#[tokio::test]
+async fn assert_retry_outcome_after_unclassified_error() {
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(fast_backoff(2)))
+        .build();
+
+    // Nothing listens on port 1, the request fails without being retried.
+    let req = client.get("http://127.0.0.1:1/foo").build().unwrap();
+    let mut extensions = http::Extensions::new();
+    extensions.insert(RetryDecisionOverride(None));
+    client
+        .execute_with_extensions(req, &mut extensions)
+        .await
+        .unwrap_err();
+
+    let outcome = extensions.get::<RetryOutcome>().unwrap();
+    assert_eq!(outcome.attempts, 1);
+    assert_eq!(
+        outcome.final_result,
+        RetryFinalResult::Failed(RetryFailureKind::Fatal)
+    );
+}
+
+#[test]
+fn assert_retryable_from_response_matches_from_reqwest_response() {
+    for status in vec![200, 302, 400, 401, 404, 408, 429, 500, 503] {
+        let response = || {
+            reqwest::Response::from(
+                http::Response::builder()
+                    .status(status)
+                    .body(Vec::<u8>::new())
+                    .unwrap(),
+            )
+        };
+        assert_eq!(
+            Retryable::from_response(&response()),
+            Retryable::from_reqwest_response(&Ok(response())),
+            "status {status}"
+        );
+    }
+}
+
+#[test]
+fn assert_retryable_from_error_matches_from_reqwest_response() {
+    let errors = || {
+        vec![
+            reqwest_middleware::Error::middleware_boxed("middleware failure".into()),
+            Client::new().get("not a url").build().unwrap_err().into(),
+        ]
+    };
+    for (error, same_error) in errors().into_iter().zip(errors()) {
+        assert_eq!(
+            Retryable::from_error(&error),
+            Retryable::from_reqwest_response(&Err(same_error))
+        );
+    }
+}
+
+#[test]
+fn assert_io_errors_are_classified_by_kind() {
+    use std::io::{Error, ErrorKind};
+
+    for &(kind, expected) in &[
+        (ErrorKind::ConnectionReset, Retryable::Transient),
+        (ErrorKind::ConnectionAborted, Retryable::Transient),
+        (ErrorKind::ConnectionRefused, Retryable::Transient),
+        (ErrorKind::TimedOut, Retryable::Fatal),
+        (ErrorKind::UnexpectedEof, Retryable::Fatal),
+        (ErrorKind::Other, Retryable::Fatal),
+    ] {
+        let err = Error::new(kind, "io error");
+        assert_eq!(Retryable::from_io_error(&err), expected, "{kind:?}");
+        assert_eq!(reqwest_retry::classify_io_error(&err), expected, "{kind:?}");
+    }
+}
+
+#[derive(Default)]
+struct MockCounter(std::sync::Mutex<Vec<RetryLabels>>);
+
+impl Counter for MockCounter {
+    fn increment(&self, labels: &RetryLabels) {
+        self.0.lock().unwrap().push(labels.clone());
+    }
+}
+
+async fn counted_outcomes(status: u16) -> Vec<(u32, RetryMetricOutcome, Option<String>)> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(RetryResponder::new(3, status))
+        .mount(&server)
+        .await;
+
+    let counter = Arc::new(MockCounter::default());
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::new_with_policy(fast_backoff(3))
+                .with_retry_counter_metric(counter.clone()),
+        )
+        .build();
+
+    client
+        .get(server.uri())
+        .with_extension(RetryUrlTemplate("/foo/{id}".into()))
+        .send()
+        .await
+        .expect("call failed");
+
+    let labels = counter.0.lock().unwrap();
+    labels
+        .iter()
+        .map(|labels| {
+            (
+                labels.attempt_number,
+                labels.outcome,
+                labels.url_template.as_deref().map(str::to_owned),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn assert_retry_counter_counts_retries_and_outcome() {
+    let template = Some("/foo/{id}".to_owned());
+    assert_eq!(
+        counted_outcomes(500).await,
+        [
+            (1, RetryMetricOutcome::Retried, template.clone()),
+            (2, RetryMetricOutcome::Succeeded, template),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn assert_retry_counter_counts_fatal_outcome() {
+    assert_eq!(
+        counted_outcomes(400).await,
+        [(
+            1,
+            RetryMetricOutcome::Failed(RetryFailureKind::Fatal),
+            Some("/foo/{id}".to_owned())
+        )]
+    );
+}
+
+#[tokio::test]
+async fn assert_retry_counter_counts_unclassified_errors_as_failures() {
+    let counter = Arc::new(MockCounter::default());
+    let client = ClientBuilder::new(Client::new())
+        .with(
+            RetryTransientMiddleware::new_with_policy(fast_backoff(2))
+                .with_retry_counter_metric(counter.clone()),
+        )
+        .build();
+
+    // Nothing listens on port 1, the request fails without being retried.
+    client
+        .get("http://127.0.0.1:1/foo")
+        .with_extension(RetryDecisionOverride(None))
+        .send()
+        .await
+        .unwrap_err();
+
+    let labels = counter.0.lock().unwrap();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].attempt_number, 1);
+    assert_eq!(
+        labels[0].outcome,
+        RetryMetricOutcome::Failed(RetryFailureKind::Fatal)
+    );
+}
+
+/// Turns error responses into `reqwest` errors carrying their status.
+struct ErrorForStatus;
+
+#[async_trait::async_trait]
+impl reqwest_middleware::Middleware for ErrorForStatus {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        extensions: &mut http::Extensions,
+        next: reqwest_middleware::Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        Ok(next.run(req, extensions).await?.error_for_status()?)
+    }
+}
+
+/// Sends a request answered with `status`, checking how many attempts were made.
+async fn send_with_error_for_status(status: u16, expected_calls: u64) {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(status))
+        .expect(expected_calls)
+        .mount(&server)
+        .await;
+
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(fast_backoff(2)))
+        .with(ErrorForStatus)
+        .build();
+
+    let err = client.get(server.uri()).send().await.unwrap_err();
+    assert!(err.is_middleware(), "{:?}", err);
+}
+
+#[tokio::test]
+async fn assert_status_errors_are_classified_by_their_status() {
+    send_with_error_for_status(503, 3).await;
+    send_with_error_for_status(404, 1).await;
+}
+
+#[tokio::test]
+async fn min_retries_are_performed_after_the_total_duration() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    // The total duration is over before the first retry.
+    let client = ClientBuilder::new(Client::new())
+        .with(RetryTransientMiddleware::new_with_policy(
+            ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(30),
+                    std::time::Duration::from_millis(100),
+                )
+                .build_with_min_retries(2, std::time::Duration::ZERO),
+        ))
+        .build();
+
+    let resp = client.get(server.uri()).send().await.expect("call failed");
+
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn assert_dry_run_records_the_retries_without_performing_them() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/transient"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/fatal"))
+        .respond_with(ResponseTemplate::new(400))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dry_run = RetryTransientMiddleware::new_dry_run(
+        ExponentialBackoff::builder().build_with_max_retries(2),
+        DefaultRetryableStrategy,
+    );
+    let records = dry_run.records();
+    let client = ClientBuilder::new(Client::new()).with(dry_run).build();
+
+    let started = std::time::Instant::now();
+    let res = client
+        .get(format!("{}/transient", server.uri()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 503);
+    // Without the dry run, the default backoff would wait at least a second before retrying.
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    let attempts: Vec<_> = records
+        .lock()
+        .unwrap()
+        .iter()
+        .map(
+            |RetryAttemptRecord {
+                 attempt, retryable, ..
+             }| (*attempt, *retryable),
+        )
+        .collect();
+    assert_eq!(
+        attempts,
+        [
+            (1, Some(Retryable::Transient)),
+            (2, Some(Retryable::Transient)),
+            (3, Some(Retryable::Transient)),
+        ]
+    );
+
+    records.lock().unwrap().clear();
+    let res = client
+        .get(format!("{}/fatal", server.uri()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].retryable, Some(Retryable::Fatal));
+}
+
+async fn dry_run_attempts<T>(retry_policy: T) -> usize
+where
+    T: retry_policies::RetryPolicy + Send + Sync + 'static,
+{
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dry_run = RetryTransientMiddleware::new_dry_run(retry_policy, DefaultRetryableStrategy);
+    let records = dry_run.records();
+    let client = ClientBuilder::new(Client::new()).with(dry_run).build();
+
+    let res = client.get(server.uri()).send().await.unwrap();
+    assert_eq!(res.status(), 503);
+    let attempts = records.lock().unwrap().len();
+    attempts
+}
+
+#[tokio::test]
+async fn assert_dry_run_simulates_the_retry_delays() {
+    // Retrying every 100ms for 2 seconds, without waiting for the 2 seconds to pass.
+    let retry_policy = ExponentialBackoff::builder()
+        .retry_bounds(
+            std::time::Duration::from_millis(100),
+            std::time::Duration::from_millis(100),
+        )
+        .jitter(retry_policies::Jitter::None)
+        .build_with_total_retry_duration(std::time::Duration::from_secs(2));
+
+    let started = std::time::Instant::now();
+    let attempts = dry_run_attempts(retry_policy).await;
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert!((20..=22).contains(&attempts), "{}", attempts);
+}
+
+#[tokio::test]
+async fn assert_dry_run_stops_policies_which_never_stop() {
+    let retry_policy = reqwest_retry::FnRetryPolicy(|_: &std::time::SystemTime, _| {
+        retry_policies::RetryDecision::Retry {
+            execute_after: std::time::SystemTime::now(),
+        }
+    });
+
+    let attempts = dry_run_attempts(retry_policy).await;
+    assert_eq!(attempts, reqwest_retry::MAX_DRY_RUN_RETRIES as usize + 1);
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("token endpoint unavailable")]
+struct TokenUnavailable {
+    retryable: bool,
+}
+
+impl reqwest_middleware::MiddlewareError for TokenUnavailable {
+    fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+}
+
+#[tokio::test]
+async fn assert_retryable_middleware_errors_are_retried() {
+    for &(retryable, expected_calls) in &[(true, 3), (false, 1)] {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counted_calls = calls.clone();
+        let client = ClientBuilder::new(Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(fast_backoff(2)))
+            .with(reqwest_middleware::middleware_fn(
+                move |_req, _ext, _next| {
+                    counted_calls.fetch_add(1, Ordering::SeqCst);
+                    Box::pin(async move {
+                        Err(reqwest_middleware::Error::from_middleware_error(
+                            TokenUnavailable { retryable },
+                        ))
+                    })
+                },
+            ))
+            .build();
+
+        let err = client
+            .get("http://localhost/")
+            .send()
+            .await
+            .expect_err("the middleware always fails");
+
+        assert!(err.is_middleware(), "{:?}", err);
+        assert_eq!(
+            calls.load(Ordering::SeqCst),
+            expected_calls,
+            "{}",
+            retryable
+        );
+    }
+}
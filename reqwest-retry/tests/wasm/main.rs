@@ -0,0 +1,101 @@
+//! Tests of the retry middleware on `wasm32-unknown-unknown`, where it sleeps with `wasmtimer`.
+//!
+//! Run them with `wasm-pack test --node reqwest-retry`.
+#![cfg(target_arch = "wasm32")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, Error, Middleware, Next, Result};
+use reqwest_retry::{
+    default_on_request_success, policies::ExponentialBackoff, RetryError, RetryTransientMiddleware,
+    Retryable, RetryableStrategy,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// Fails the first `failures` attempts with a middleware error, then answers from a `data:` URL
+/// since the test runner has no server to send the request to.
+struct FlakyMiddleware {
+    calls: Arc<AtomicU32>,
+    failures: u32,
+}
+
+#[async_trait::async_trait(?Send)]
+impl Middleware for FlakyMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err(Error::middleware_boxed("simulated 500".into()));
+        }
+        *req.url_mut() = "data:text/plain,ok".parse().unwrap();
+        next.run(req, extensions).await
+    }
+}
+
+/// Retries the errors of [`FlakyMiddleware`], which are fatal for the default strategy.
+struct RetryMiddlewareErrors;
+
+impl RetryableStrategy for RetryMiddlewareErrors {
+    fn handle(&self, res: &Result<Response>) -> Option<Retryable> {
+        match res {
+            Ok(success) => default_on_request_success(success),
+            Err(_) => Some(Retryable::Transient),
+        }
+    }
+}
+
+async fn send_with_failures(failures: u32) -> (Result<Response>, u32) {
+    let calls = Arc::new(AtomicU32::new(0));
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+            ExponentialBackoff::builder()
+                .retry_bounds(Duration::from_millis(1), Duration::from_millis(1))
+                .build_with_max_retries(2),
+            RetryMiddlewareErrors,
+        ))
+        .with(FlakyMiddleware {
+            calls: calls.clone(),
+            failures,
+        })
+        .build();
+
+    let res = client.get("http://localhost/").send().await;
+    (res, calls.load(Ordering::SeqCst))
+}
+
+#[wasm_bindgen_test]
+async fn retries_until_success() {
+    let (res, calls) = send_with_failures(1).await;
+
+    let res = res.expect("the retry should succeed");
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.text().await.unwrap(), "ok");
+    assert_eq!(calls, 2);
+}
+
+#[wasm_bindgen_test]
+async fn gives_up_after_the_max_retries() {
+    let (res, calls) = send_with_failures(u32::MAX).await;
+
+    let err = res.expect_err("all the attempts fail");
+    let retry_error = match &err {
+        Error::Middleware(e) => e.downcast_ref::<RetryError>(),
+        Error::Reqwest(_) => None,
+    };
+    assert!(
+        matches!(
+            retry_error,
+            Some(RetryError::RetriesExhausted { attempts: 3, .. })
+        ),
+        "{:?}",
+        err
+    );
+    assert_eq!(calls, 3);
+}
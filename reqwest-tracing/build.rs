@@ -0,0 +1,30 @@
+//! Warns when several `opentelemetry_*` features are enabled at once.
+//!
+//! This is supported, the context is propagated with each version, but it's rarely intended: it
+//! usually comes from a dependency enabling a different version than the application, and only the
+//! most recent version is used by the APIs taking a single OpenTelemetry type, such as
+//! `TraceContextExtractor`.
+
+const OTEL_VERSIONS: &[&str] = &[
+    "0_20", "0_21", "0_22", "0_23", "0_24", "0_25", "0_26", "0_27",
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let enabled: Vec<String> = OTEL_VERSIONS
+        .iter()
+        .filter(|version| {
+            std::env::var_os(format!("CARGO_FEATURE_OPENTELEMETRY_{}", version)).is_some()
+        })
+        .map(|version| format!("opentelemetry_{}", version))
+        .collect();
+
+    if enabled.len() > 1 {
+        println!(
+            "cargo:warning=multiple OpenTelemetry versions are enabled ({}), \
+             consider disabling all but the one your application uses",
+            enabled.join(", ")
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Propagation of [W3C Baggage](https://www.w3.org/TR/baggage/) from incoming requests to the
+//! outgoing requests made while handling them.
+//!
+//! ```no_run
+//! use reqwest_tracing::baggage::{extract_baggage_from_headers, inject_baggage_into_extensions};
+//! # async fn example(
+//! #     client: reqwest_middleware::ClientWithMiddleware,
+//! #     incoming_headers: http::HeaderMap,
+//! # ) -> reqwest_middleware::Result<()> {
+//! let baggage = extract_baggage_from_headers(&incoming_headers);
+//!
+//! let request = client.get("https://truelayer.com").build()?;
+//! let mut extensions = http::Extensions::new();
+//! inject_baggage_into_extensions(baggage, &mut extensions);
+//! client.execute_with_extensions(request, &mut extensions).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Baggage is only extracted and injected if the global text map propagator handles it, e.g. if it
+//! is a `TextMapCompositePropagator` including a `BaggagePropagator`.
+use http::{Extensions, HeaderMap};
+
+use crate::TraceContextExtractor;
+
+/// The baggage extracted from the headers of an incoming request, see
+/// [`extract_baggage_from_headers`].
+///
+/// When present in the request [`Extensions`], e.g. with [`inject_baggage_into_extensions`],
+/// [`TracingMiddleware`](crate::TracingMiddleware) adds its entries to the baggage of the
+/// propagated context.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BaggageContext {
+    // (name, value, metadata)
+    entries: Vec<(String, String, String)>,
+}
+
+impl BaggageContext {
+    /// Returns the value of the baggage entry `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _, _)| entry_name == name)
+            .map(|(_, value, _)| value.as_str())
+    }
+
+    /// Returns `true` if there is no baggage entry.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (String, String, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value, metadata)| (name.clone(), value.clone(), metadata.as_str()))
+    }
+}
+
+/// Extracts the baggage propagated in the headers of an incoming request, using the global text
+/// map propagator of the most recent `opentelemetry` version enabled.
+pub fn extract_baggage_from_headers(headers: &HeaderMap) -> BaggageContext {
+    BaggageContext::from(&TraceContextExtractor::extract_from_headers(headers))
+}
+
+/// Inserts the `baggage` in the request extensions, for [`TracingMiddleware`] to propagate it.
+///
+/// [`TracingMiddleware`]: crate::TracingMiddleware
+pub fn inject_baggage_into_extensions(baggage: BaggageContext, ext: &mut Extensions) {
+    ext.insert(baggage);
+}
+
+#[cfg(feature = "opentelemetry_0_20")]
+impl From<&opentelemetry_0_20_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_20_pkg::Context) -> Self {
+        use opentelemetry_0_20_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_21")]
+impl From<&opentelemetry_0_21_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_21_pkg::Context) -> Self {
+        use opentelemetry_0_21_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_22")]
+impl From<&opentelemetry_0_22_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_22_pkg::Context) -> Self {
+        use opentelemetry_0_22_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_23")]
+impl From<&opentelemetry_0_23_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_23_pkg::Context) -> Self {
+        use opentelemetry_0_23_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_24")]
+impl From<&opentelemetry_0_24_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_24_pkg::Context) -> Self {
+        use opentelemetry_0_24_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_25")]
+impl From<&opentelemetry_0_25_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_25_pkg::Context) -> Self {
+        use opentelemetry_0_25_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_26")]
+impl From<&opentelemetry_0_26_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_26_pkg::Context) -> Self {
+        use opentelemetry_0_26_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_27")]
+impl From<&opentelemetry_0_27_pkg::Context> for BaggageContext {
+    fn from(context: &opentelemetry_0_27_pkg::Context) -> Self {
+        use opentelemetry_0_27_pkg::baggage::BaggageExt;
+        let entries = context
+            .baggage()
+            .iter()
+            .map(|(name, (value, metadata))| {
+                (
+                    name.as_str().to_owned(),
+                    value.to_string(),
+                    metadata.as_str().to_owned(),
+                )
+            })
+            .collect();
+        BaggageContext { entries }
+    }
+}
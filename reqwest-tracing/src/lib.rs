@@ -82,6 +82,33 @@
 //!     .build();
 //! ```
 
+#[cfg(any(
+    feature = "opentelemetry_0_20",
+    feature = "opentelemetry_0_21",
+    feature = "opentelemetry_0_22",
+    feature = "opentelemetry_0_23",
+    feature = "opentelemetry_0_24",
+    feature = "opentelemetry_0_25",
+    feature = "opentelemetry_0_26",
+    feature = "opentelemetry_0_27",
+))]
+pub mod baggage;
+#[cfg(feature = "url-hash")]
+mod hashed_url;
+#[cfg(all(
+    feature = "metrics",
+    any(
+        feature = "opentelemetry_0_20",
+        feature = "opentelemetry_0_21",
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27",
+    )
+))]
+mod metrics;
 mod middleware;
 #[cfg(any(
     feature = "opentelemetry_0_20",
@@ -95,13 +122,47 @@ mod middleware;
 ))]
 mod otel;
 mod reqwest_otel_span_builder;
-pub use middleware::TracingMiddleware;
+#[cfg(feature = "url-hash")]
+pub use hashed_url::SpanBackendWithHashedUrl;
+#[cfg(all(
+    feature = "metrics",
+    any(
+        feature = "opentelemetry_0_20",
+        feature = "opentelemetry_0_21",
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27",
+    )
+))]
+pub use metrics::SpanBackendWithMetrics;
+pub use middleware::{
+    AsyncTracingMiddleware, DynamicSpanBackend, FnSpanBackend, TracingMiddleware,
+};
+#[cfg(any(
+    feature = "opentelemetry_0_20",
+    feature = "opentelemetry_0_21",
+    feature = "opentelemetry_0_22",
+    feature = "opentelemetry_0_23",
+    feature = "opentelemetry_0_24",
+    feature = "opentelemetry_0_25",
+    feature = "opentelemetry_0_26",
+    feature = "opentelemetry_0_27",
+))]
+pub use otel::{RequestCarrier, ResponseExtractor, TraceContextExtractor};
 pub use reqwest_otel_span_builder::{
     default_on_request_end, default_on_request_failure, default_on_request_success,
-    default_span_name, DefaultSpanBackend, DisableOtelPropagation, OtelName, OtelPathNames,
-    ReqwestOtelSpanBackend, SpanBackendWithUrl, ERROR_CAUSE_CHAIN, ERROR_MESSAGE,
-    HTTP_REQUEST_METHOD, HTTP_RESPONSE_STATUS_CODE, OTEL_KIND, OTEL_NAME, OTEL_STATUS_CODE,
-    SERVER_ADDRESS, SERVER_PORT, URL_FULL, URL_SCHEME, USER_AGENT_ORIGINAL,
+    default_span_name, AsyncReqwestOtelSpanBackend, DefaultSpanBackend, DisableOtelPropagation,
+    ErasedSpanBackend, ExcludedUrls, OtelHeaderPropagationFilter, OtelKind, OtelName,
+    OtelPathNames, OtelPathNamesBuilder, OtelUrlTemplate, PeerIp, ReqwestOtelSpanBackend,
+    SpanBackendWithResponseHeaders, SpanBackendWithTiming, SpanBackendWithUrl,
+    SpanBackendWithUrlSanitized, ERROR_CAUSE_CHAIN, ERROR_MESSAGE, ERROR_TYPE,
+    HTTP_CLIENT_DURATION, HTTP_REQUEST_ID, HTTP_REQUEST_METHOD, HTTP_REQUEST_RESEND_COUNT,
+    HTTP_RESPONSE_STATUS_CODE, NETWORK_PEER_ADDRESS, NETWORK_PEER_PORT, OTEL_KIND,
+    OTEL_LIBRARY_NAME, OTEL_LIBRARY_VERSION, OTEL_NAME, OTEL_STATUS_CODE, OTEL_STATUS_OK,
+    SERVER_ADDRESS, SERVER_PORT, URL_FULL, URL_SCHEME, URL_TEMPLATE, USER_AGENT_ORIGINAL,
 };
 
 #[cfg(feature = "deprecated_attributes")]
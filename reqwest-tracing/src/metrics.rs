@@ -0,0 +1,393 @@
+//! Metrics recorded alongside the spans, see [`SpanBackendWithMetrics`].
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::Result;
+use tracing::Span;
+
+#[cfg(all(
+    feature = "opentelemetry_0_20",
+    not(any(
+        feature = "opentelemetry_0_21",
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+use opentelemetry_0_20_pkg as opentelemetry;
+
+#[cfg(all(
+    feature = "opentelemetry_0_21",
+    not(any(
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+use opentelemetry_0_21_pkg as opentelemetry;
+
+#[cfg(all(
+    feature = "opentelemetry_0_22",
+    not(any(
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+use opentelemetry_0_22_pkg as opentelemetry;
+
+#[cfg(all(
+    feature = "opentelemetry_0_23",
+    not(any(
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+use opentelemetry_0_23_pkg as opentelemetry;
+
+#[cfg(all(
+    feature = "opentelemetry_0_24",
+    not(any(
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+use opentelemetry_0_24_pkg as opentelemetry;
+
+#[cfg(all(
+    feature = "opentelemetry_0_25",
+    not(any(feature = "opentelemetry_0_26", feature = "opentelemetry_0_27"))
+))]
+use opentelemetry_0_25_pkg as opentelemetry;
+
+#[cfg(all(feature = "opentelemetry_0_26", not(feature = "opentelemetry_0_27")))]
+use opentelemetry_0_26_pkg as opentelemetry;
+
+#[cfg(feature = "opentelemetry_0_27")]
+use opentelemetry_0_27_pkg as opentelemetry;
+
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::KeyValue;
+
+use crate::reqwest_otel_span_builder::{
+    default_on_request_end, DefaultSpanBackend, ReqwestOtelSpanBackend, HTTP_REQUEST_METHOD,
+    HTTP_RESPONSE_STATUS_CODE, SERVER_ADDRESS,
+};
+
+/// Finishes an instrument builder, `init` was renamed to `build` in opentelemetry 0.27.
+#[cfg(not(feature = "opentelemetry_0_27"))]
+macro_rules! build {
+    ($builder:expr $(,)?) => {
+        $builder.init()
+    };
+}
+
+#[cfg(feature = "opentelemetry_0_27")]
+macro_rules! build {
+    ($builder:expr $(,)?) => {
+        $builder.build()
+    };
+}
+
+/// Name of the meter used by [`SpanBackendWithMetrics::default`].
+const METER_NAME: &str = "reqwest";
+
+/// Similar to [`DefaultSpanBackend`] but also records OpenTelemetry metrics for each request:
+/// - `http.client.request.duration`, a histogram of the request durations in seconds,
+/// - `http.client.request.count`, a counter of the requests.
+///
+/// Both have the `http.request.method`, `server.address` and `http.response.status_code` (when a
+/// response was received) attributes.
+///
+/// The instruments are created from the meter of the most recent `opentelemetry` version enabled.
+/// By default, they are created on the first request with the `reqwest` meter of the global meter
+/// provider, which must be set by then.
+///
+/// Like the other span backends, the middleware doesn't hold a `SpanBackendWithMetrics`. To use
+/// another meter, create one with [`SpanBackendWithMetrics::new`] and insert it in the request
+/// extensions, e.g. with a [`reqwest_middleware::Extension`] initialiser.
+///
+/// Usage:
+/// ```no_run
+/// # use reqwest_middleware::Result;
+/// use reqwest_middleware::ClientBuilder;
+/// use reqwest_tracing::{SpanBackendWithMetrics, TracingMiddleware};
+/// # async fn example() -> Result<()> {
+/// // Uses the "reqwest" meter of the global meter provider.
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with(TracingMiddleware::<SpanBackendWithMetrics>::new())
+///     .build();
+///
+/// let resp = client.get("https://truelayer.com").send().await.unwrap();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// With another meter:
+/// ```
+/// use reqwest_middleware::{ClientBuilder, Extension};
+/// use reqwest_tracing::{SpanBackendWithMetrics, TracingMiddleware};
+///
+/// # fn example(metrics: SpanBackendWithMetrics) {
+/// // `metrics` is e.g. `SpanBackendWithMetrics::new(&meter_provider.meter("my-service"))`.
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with_init(Extension(metrics))
+///     .with(TracingMiddleware::<SpanBackendWithMetrics>::new())
+///     .build();
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SpanBackendWithMetrics {
+    duration: Histogram<f64>,
+    count: Counter<u64>,
+}
+
+impl SpanBackendWithMetrics {
+    /// Creates the instruments with `meter`, to be inserted in the request extensions.
+    pub fn new(meter: &Meter) -> Self {
+        SpanBackendWithMetrics {
+            duration: build!(meter
+                .f64_histogram("http.client.request.duration")
+                .with_description("Duration of HTTP client requests.")
+                .with_unit(seconds()),),
+            count: build!(meter
+                .u64_counter("http.client.request.count")
+                .with_description("Number of HTTP client requests."),),
+        }
+    }
+}
+
+impl Default for SpanBackendWithMetrics {
+    /// Creates the instruments with the `reqwest` meter of the global meter provider.
+    fn default() -> Self {
+        Self::new(&opentelemetry::global::meter(METER_NAME))
+    }
+}
+
+/// The instruments used for requests without a [`SpanBackendWithMetrics`] extension, created on
+/// first use.
+fn default_metrics() -> &'static SpanBackendWithMetrics {
+    static DEFAULT_METRICS: OnceLock<SpanBackendWithMetrics> = OnceLock::new();
+    DEFAULT_METRICS.get_or_init(SpanBackendWithMetrics::default)
+}
+
+/// The start and attributes of a request measured by [`SpanBackendWithMetrics`].
+#[derive(Clone)]
+struct RequestMeasure {
+    start: Instant,
+    attributes: Vec<KeyValue>,
+}
+
+impl ReqwestOtelSpanBackend for SpanBackendWithMetrics {
+    const SPAN_KIND: &'static str = "client";
+
+    fn on_request_start(req: &Request, ext: &mut Extensions) -> Span {
+        let mut attributes = vec![KeyValue::new(
+            HTTP_REQUEST_METHOD,
+            req.method().as_str().to_owned(),
+        )];
+        if let Some(host) = req.url().host_str() {
+            attributes.push(KeyValue::new(SERVER_ADDRESS, host.to_owned()));
+        }
+        ext.insert(RequestMeasure {
+            start: Instant::now(),
+            attributes,
+        });
+        DefaultSpanBackend::on_request_start(req, ext)
+    }
+
+    fn on_request_end(span: &Span, outcome: &Result<Response>, ext: &mut Extensions) {
+        default_on_request_end(span, outcome);
+        if let Some(mut measure) = ext.remove::<RequestMeasure>() {
+            let metrics = ext.get::<Self>().unwrap_or_else(|| default_metrics());
+            if let Ok(response) = outcome {
+                measure.attributes.push(KeyValue::new(
+                    HTTP_RESPONSE_STATUS_CODE,
+                    i64::from(response.status().as_u16()),
+                ));
+            }
+            metrics
+                .duration
+                .record(measure.start.elapsed().as_secs_f64(), &measure.attributes);
+            metrics.count.add(1, &measure.attributes);
+        }
+    }
+}
+
+#[cfg(not(any(
+    feature = "opentelemetry_0_24",
+    feature = "opentelemetry_0_25",
+    feature = "opentelemetry_0_26",
+    feature = "opentelemetry_0_27"
+)))]
+/// The unit of the request durations.
+fn seconds() -> opentelemetry::metrics::Unit {
+    opentelemetry::metrics::Unit::new("s")
+}
+
+#[cfg(any(
+    feature = "opentelemetry_0_24",
+    feature = "opentelemetry_0_25",
+    feature = "opentelemetry_0_26",
+    feature = "opentelemetry_0_27"
+))]
+/// The unit of the request durations.
+fn seconds() -> &'static str {
+    "s"
+}
+
+#[cfg(all(test, feature = "opentelemetry_0_27"))]
+mod tests {
+    use super::*;
+    use crate::TracingMiddleware;
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk_0_27::metrics::{
+        data::{Histogram as HistogramData, ResourceMetrics, Sum},
+        InstrumentKind, ManualReader, MetricResult, Pipeline, SdkMeterProvider, Temporality,
+    };
+    use opentelemetry_sdk_0_27::{metrics::reader::MetricReader, Resource};
+    use reqwest_middleware::{ClientBuilder, Extension};
+    use std::sync::{Arc, Weak};
+    use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
+
+    /// A [`ManualReader`] which can still be used once given to the meter provider.
+    #[derive(Clone, Debug)]
+    struct SharedReader(Arc<ManualReader>);
+
+    impl MetricReader for SharedReader {
+        fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+            self.0.register_pipeline(pipeline)
+        }
+
+        fn collect(&self, rm: &mut ResourceMetrics) -> MetricResult<()> {
+            self.0.collect(rm)
+        }
+
+        fn force_flush(&self) -> MetricResult<()> {
+            self.0.force_flush()
+        }
+
+        fn shutdown(&self) -> MetricResult<()> {
+            self.0.shutdown()
+        }
+
+        fn temporality(&self, kind: InstrumentKind) -> Temporality {
+            self.0.temporality(kind)
+        }
+    }
+
+    #[tokio::test]
+    async fn records_request_duration_and_count() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let reader = SharedReader(Arc::new(ManualReader::builder().build()));
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader.clone())
+            .build();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(Extension(SpanBackendWithMetrics::new(
+                &provider.meter("reqwest-tracing-test"),
+            )))
+            .with(TracingMiddleware::<SpanBackendWithMetrics>::new())
+            .build();
+
+        client.get(server.uri()).send().await.unwrap();
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![],
+        };
+        reader.collect(&mut metrics).unwrap();
+        assert_eq!(
+            metrics.scope_metrics[0].scope.name(),
+            "reqwest-tracing-test"
+        );
+        let metrics = &metrics.scope_metrics[0].metrics;
+        let metric = |name: &str| {
+            metrics
+                .iter()
+                .find(|metric| metric.name == name)
+                .unwrap()
+                .data
+                .as_any()
+        };
+
+        let duration = metric("http.client.request.duration")
+            .downcast_ref::<HistogramData<f64>>()
+            .unwrap();
+        assert_eq!(duration.data_points.len(), 1);
+        assert_eq!(duration.data_points[0].count, 1);
+        let attributes = &duration.data_points[0].attributes;
+        for expected in [
+            KeyValue::new(HTTP_REQUEST_METHOD, "GET"),
+            KeyValue::new(SERVER_ADDRESS, "127.0.0.1"),
+            KeyValue::new(HTTP_RESPONSE_STATUS_CODE, 200),
+        ]
+        .iter()
+        {
+            assert!(attributes.contains(expected), "{:?}", attributes);
+        }
+
+        let count = metric("http.client.request.count")
+            .downcast_ref::<Sum<u64>>()
+            .unwrap();
+        assert_eq!(count.data_points.len(), 1);
+        assert_eq!(count.data_points[0].value, 1);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_global_meter_provider() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let reader = SharedReader(Arc::new(ManualReader::builder().build()));
+        opentelemetry::global::set_meter_provider(
+            SdkMeterProvider::builder()
+                .with_reader(reader.clone())
+                .build(),
+        );
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::<SpanBackendWithMetrics>::new())
+            .build();
+
+        client.get(server.uri()).send().await.unwrap();
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![],
+        };
+        reader.collect(&mut metrics).unwrap();
+        let scope = &metrics.scope_metrics[0];
+        assert_eq!(scope.scope.name(), METER_NAME);
+        let count = scope
+            .metrics
+            .iter()
+            .find(|metric| metric.name == "http.client.request.count")
+            .unwrap()
+            .data
+            .as_any()
+            .downcast_ref::<Sum<u64>>()
+            .unwrap();
+        assert_eq!(count.data_points[0].value, 1);
+    }
+}
@@ -1,26 +1,148 @@
+use std::sync::Arc;
+
 use http::Extensions;
 use reqwest::{Request, Response};
-use reqwest_middleware::{Middleware, Next, Result};
-use tracing::Instrument;
+use reqwest_middleware::{Middleware, Next, ResendCount, Result};
+use tracing::{Instrument, Span};
 
-use crate::{DefaultSpanBackend, ReqwestOtelSpanBackend};
+use crate::reqwest_otel_span_builder::{record_peer_ip, record_request_id, record_url_template};
+use crate::{
+    AsyncReqwestOtelSpanBackend, DefaultSpanBackend, ErasedSpanBackend, ExcludedUrls, OtelKind,
+    OtelUrlTemplate, ReqwestOtelSpanBackend, HTTP_REQUEST_RESEND_COUNT, OTEL_KIND,
+};
 
 /// Middleware for tracing requests using the current Opentelemetry Context.
-pub struct TracingMiddleware<S: ReqwestOtelSpanBackend> {
+pub struct TracingMiddleware<S> {
     span_backend: std::marker::PhantomData<S>,
+    /// Only set for span backends with state, i.e. [`FnSpanBackend`] and [`DynamicSpanBackend`],
+    /// the others are only used through their associated functions.
+    stateful_span_backend: Option<S>,
+    log_on_error: bool,
+    excluded_urls: ExcludedUrls,
 }
 
 impl<S: ReqwestOtelSpanBackend> TracingMiddleware<S> {
     pub fn new() -> TracingMiddleware<S> {
+        Self::from_span_backend(None)
+    }
+}
+
+impl<F1, F2> TracingMiddleware<FnSpanBackend<F1, F2>>
+where
+    F1: Fn(&Request, &mut Extensions) -> Span + Send + Sync + 'static,
+    F2: Fn(&Span, &Result<Response>, &mut Extensions) + Send + Sync + 'static,
+{
+    /// Creates a middleware with a span backend made of two closures, for one-off customisations
+    /// that don't warrant implementing [`ReqwestOtelSpanBackend`].
+    ///
+    /// `start` creates the span of each request, like [`ReqwestOtelSpanBackend::on_request_start`],
+    /// and `end` records the outcome on it, like [`ReqwestOtelSpanBackend::on_request_end`]. The
+    /// spans are `client` spans, unless the request has an [`OtelKind`] extension.
+    ///
+    /// ```
+    /// use reqwest_middleware::ClientBuilder;
+    /// use reqwest_tracing::{default_on_request_end, reqwest_otel_span, TracingMiddleware};
+    ///
+    /// let client = ClientBuilder::new(reqwest::Client::new())
+    ///     .with(TracingMiddleware::with_span_backend_fn(
+    ///         |req, _| reqwest_otel_span!(name = "payments-api", req),
+    ///         |span, outcome, _| default_on_request_end(span, outcome),
+    ///     ))
+    ///     .build();
+    /// ```
+    pub fn with_span_backend_fn(start: F1, end: F2) -> Self {
+        Self::from_span_backend(Some(FnSpanBackend {
+            start,
+            headers_sent: None,
+            end,
+        }))
+    }
+
+    /// Runs `headers_sent` once the request is handed over to send, like
+    /// [`ReqwestOtelSpanBackend::on_request_headers_sent`]. Does nothing by default.
+    pub fn with_headers_sent_fn<F>(mut self, headers_sent: F) -> Self
+    where
+        F: Fn(&Span, &Request, &mut Extensions) + Send + Sync + 'static,
+    {
+        if let Some(span_backend) = &mut self.stateful_span_backend {
+            span_backend.headers_sent = Some(Arc::new(headers_sent));
+        }
+        self
+    }
+}
+
+impl TracingMiddleware<DynamicSpanBackend> {
+    /// Creates a middleware choosing the span backend of each request with a
+    /// [`DynamicSpanBackend`].
+    pub fn with_dynamic_span_backend(span_backend: DynamicSpanBackend) -> Self {
+        Self::from_span_backend(Some(span_backend))
+    }
+}
+
+impl<S> TracingMiddleware<S> {
+    fn from_span_backend(stateful_span_backend: Option<S>) -> Self {
+        TracingMiddleware {
+            span_backend: Default::default(),
+            stateful_span_backend,
+            log_on_error: false,
+            excluded_urls: ExcludedUrls::default(),
+        }
+    }
+
+    /// Emit a `WARN` event within the request span for responses with a 4xx status, with the
+    /// `http.response.status_code`, the `error.message` and the `url.template` if known, see
+    /// [`OtelPathNames`](crate::OtelPathNames). Disabled by default.
+    ///
+    /// The event doesn't include the URL itself, which is only part of the span if the span
+    /// backend records it. The span is still marked as failed either way, this makes the failure
+    /// visible to subscribers that only output events, e.g. when tailing logs.
+    pub fn with_log_on_error(mut self, enabled: bool) -> Self {
+        self.log_on_error = enabled;
+        self
+    }
+
+    /// Don't create a span for requests whose path matches one of the `patterns`, see
+    /// [`ExcludedUrls`]. These requests are sent as they are, without propagating the
+    /// OpenTelemetry context either.
+    ///
+    /// An [`ExcludedUrls`] request extension takes priority over these patterns.
+    pub fn with_excluded_urls<I, P>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.excluded_urls = ExcludedUrls::new(patterns);
+        self
+    }
+}
+
+impl<S> TracingMiddleware<S> {
+    /// Clones the configuration of the middleware, with the given span backend.
+    fn clone_with(&self, stateful_span_backend: Option<S>) -> Self {
         TracingMiddleware {
             span_backend: Default::default(),
+            stateful_span_backend,
+            log_on_error: self.log_on_error,
+            excluded_urls: self.excluded_urls.clone(),
         }
     }
 }
 
 impl<S: ReqwestOtelSpanBackend> Clone for TracingMiddleware<S> {
     fn clone(&self) -> Self {
-        Self::new()
+        self.clone_with(None)
+    }
+}
+
+impl<F1: Clone, F2: Clone> Clone for TracingMiddleware<FnSpanBackend<F1, F2>> {
+    fn clone(&self) -> Self {
+        self.clone_with(self.stateful_span_backend.clone())
+    }
+}
+
+impl Clone for TracingMiddleware<DynamicSpanBackend> {
+    fn clone(&self) -> Self {
+        self.clone_with(self.stateful_span_backend.clone())
     }
 }
 
@@ -42,33 +164,1127 @@ where
         extensions: &mut Extensions,
         next: Next<'_>,
     ) -> Result<Response> {
-        let request_span = ReqwestOtelSpan::on_request_start(&req, extensions);
+        if is_excluded(&self.excluded_urls, &req, extensions) {
+            return next.run(req, extensions).await;
+        }
+        let span_backend = StaticSpanBackend::<ReqwestOtelSpan>(std::marker::PhantomData);
+        self.trace(&span_backend, req, extensions, next).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<F1, F2> Middleware for TracingMiddleware<FnSpanBackend<F1, F2>>
+where
+    F1: Fn(&Request, &mut Extensions) -> Span + Send + Sync + 'static,
+    F2: Fn(&Span, &Result<Response>, &mut Extensions) + Send + Sync + 'static,
+{
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if is_excluded(&self.excluded_urls, &req, extensions) {
+            return next.run(req, extensions).await;
+        }
+        let span_backend = self
+            .stateful_span_backend
+            .as_ref()
+            .expect("`with_span_backend_fn` always sets the span backend");
+        self.trace(span_backend, req, extensions, next).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for TracingMiddleware<DynamicSpanBackend> {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if is_excluded(&self.excluded_urls, &req, extensions) {
+            return next.run(req, extensions).await;
+        }
+        let span_backend = self
+            .stateful_span_backend
+            .as_ref()
+            .expect("`with_dynamic_span_backend` always sets the span backend");
+        let span_backend = (span_backend.selector)(&req);
+        self.trace(span_backend, req, extensions, next).await
+    }
+}
+
+impl<S> TracingMiddleware<S> {
+    /// Runs the request within a span created by `span_backend`.
+    async fn trace(
+        &self,
+        span_backend: &dyn ErasedSpanBackend,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let request_span = span_backend.start_span(&req, extensions);
+        record_common_fields(&request_span, span_backend.span_kind(), &req, extensions);
 
         let outcome_future = async {
-            #[cfg(any(
-                feature = "opentelemetry_0_20",
-                feature = "opentelemetry_0_21",
-                feature = "opentelemetry_0_22",
-                feature = "opentelemetry_0_23",
-                feature = "opentelemetry_0_24",
-                feature = "opentelemetry_0_25",
-                feature = "opentelemetry_0_26",
-                feature = "opentelemetry_0_27",
-            ))]
-            let req = if extensions.get::<crate::DisableOtelPropagation>().is_none() {
-                // Adds tracing headers to the given request to propagate the OpenTelemetry context to downstream revivers of the request.
-                // Spans added by downstream consumers will be part of the same trace.
-                crate::otel::inject_opentelemetry_context_into_request(req)
-            } else {
-                req
-            };
+            let req = propagate_context(req, extensions);
+            span_backend.headers_sent(&request_span, &req, extensions);
 
             // Run the request
             let outcome = next.run(req, extensions).await;
-            ReqwestOtelSpan::on_request_end(&request_span, &outcome, extensions);
+            if self.log_on_error {
+                log_error_response(&request_span, &outcome, extensions);
+            }
+            span_backend.end_span(&request_span, &outcome, extensions);
             outcome
         };
 
         outcome_future.instrument(request_span.clone()).await
     }
 }
+
+/// Calls the associated functions of a [`ReqwestOtelSpanBackend`], which the middleware doesn't
+/// hold an instance of.
+struct StaticSpanBackend<S>(std::marker::PhantomData<S>);
+
+impl<S: ReqwestOtelSpanBackend + Send + Sync> ErasedSpanBackend for StaticSpanBackend<S> {
+    fn span_kind(&self) -> &'static str {
+        S::SPAN_KIND
+    }
+
+    fn start_span(&self, req: &Request, extension: &mut Extensions) -> Span {
+        S::on_request_start(req, extension)
+    }
+
+    fn headers_sent(&self, span: &Span, req: &Request, extension: &mut Extensions) {
+        S::on_request_headers_sent(span, req, extension)
+    }
+
+    fn end_span(&self, span: &Span, outcome: &Result<Response>, extension: &mut Extensions) {
+        S::on_request_end(span, outcome, extension)
+    }
+}
+
+/// A span backend made of two closures, see [`TracingMiddleware::with_span_backend_fn`].
+///
+/// Unlike the [`ReqwestOtelSpanBackend`] implementations, it holds state and is stored in the
+/// middleware.
+#[derive(Clone)]
+pub struct FnSpanBackend<F1, F2> {
+    start: F1,
+    headers_sent: Option<HeadersSentFn>,
+    end: F2,
+}
+
+type HeadersSentFn = Arc<dyn Fn(&Span, &Request, &mut Extensions) + Send + Sync>;
+
+impl<F1, F2> ErasedSpanBackend for FnSpanBackend<F1, F2>
+where
+    F1: Fn(&Request, &mut Extensions) -> Span + Send + Sync,
+    F2: Fn(&Span, &Result<Response>, &mut Extensions) + Send + Sync,
+{
+    fn span_kind(&self) -> &'static str {
+        DefaultSpanBackend::SPAN_KIND
+    }
+
+    fn start_span(&self, req: &Request, extension: &mut Extensions) -> Span {
+        (self.start)(req, extension)
+    }
+
+    fn headers_sent(&self, span: &Span, req: &Request, extension: &mut Extensions) {
+        if let Some(headers_sent) = &self.headers_sent {
+            headers_sent(span, req, extension);
+        }
+    }
+
+    fn end_span(&self, span: &Span, outcome: &Result<Response>, extension: &mut Extensions) {
+        (self.end)(span, outcome, extension)
+    }
+}
+
+/// A span backend choosing another span backend for each request, e.g. to record the URL of
+/// internal requests only. Use it with [`TracingMiddleware::with_dynamic_span_backend`].
+///
+/// The selector returns an [`ErasedSpanBackend`], which any [`ReqwestOtelSpanBackend`]
+/// implements.
+///
+/// ```
+/// use reqwest_middleware::ClientBuilder;
+/// use reqwest_tracing::{
+///     DefaultSpanBackend, DynamicSpanBackend, ErasedSpanBackend, SpanBackendWithUrl,
+///     TracingMiddleware,
+/// };
+///
+/// let span_backend = DynamicSpanBackend::new(|req| -> &'static dyn ErasedSpanBackend {
+///     if req.url().host_str() == Some("internal.example.com") {
+///         &SpanBackendWithUrl
+///     } else {
+///         &DefaultSpanBackend
+///     }
+/// });
+/// let client = ClientBuilder::new(reqwest::Client::new())
+///     .with(TracingMiddleware::with_dynamic_span_backend(span_backend))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct DynamicSpanBackend {
+    selector: Arc<dyn Fn(&Request) -> &'static (dyn ErasedSpanBackend) + Send + Sync>,
+}
+
+impl DynamicSpanBackend {
+    /// Create a backend picking the span backend of every request with `selector`.
+    pub fn new<F>(selector: F) -> Self
+    where
+        F: Fn(&Request) -> &'static dyn ErasedSpanBackend,
+        F: Send + Sync + 'static,
+    {
+        DynamicSpanBackend {
+            selector: Arc::new(selector),
+        }
+    }
+}
+
+/// Middleware for tracing requests using the current Opentelemetry Context, with an
+/// [`AsyncReqwestOtelSpanBackend`].
+///
+/// Prefer [`TracingMiddleware`] unless the span backend needs to perform async operations.
+pub struct AsyncTracingMiddleware<S: AsyncReqwestOtelSpanBackend> {
+    span_backend: std::marker::PhantomData<S>,
+    log_on_error: bool,
+    excluded_urls: ExcludedUrls,
+}
+
+impl<S: AsyncReqwestOtelSpanBackend> AsyncTracingMiddleware<S> {
+    pub fn new() -> AsyncTracingMiddleware<S> {
+        AsyncTracingMiddleware {
+            span_backend: Default::default(),
+            log_on_error: false,
+            excluded_urls: ExcludedUrls::default(),
+        }
+    }
+
+    /// Emit a `WARN` event within the request span for responses with a 4xx status, see
+    /// [`TracingMiddleware::with_log_on_error`].
+    pub fn with_log_on_error(mut self, enabled: bool) -> Self {
+        self.log_on_error = enabled;
+        self
+    }
+
+    /// Don't create a span for requests whose path matches one of the `patterns`, see
+    /// [`TracingMiddleware::with_excluded_urls`].
+    pub fn with_excluded_urls<I, P>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.excluded_urls = ExcludedUrls::new(patterns);
+        self
+    }
+}
+
+impl<S: AsyncReqwestOtelSpanBackend> Clone for AsyncTracingMiddleware<S> {
+    fn clone(&self) -> Self {
+        AsyncTracingMiddleware {
+            span_backend: Default::default(),
+            log_on_error: self.log_on_error,
+            excluded_urls: self.excluded_urls.clone(),
+        }
+    }
+}
+
+impl<S: AsyncReqwestOtelSpanBackend> Default for AsyncTracingMiddleware<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<ReqwestOtelSpan> Middleware for AsyncTracingMiddleware<ReqwestOtelSpan>
+where
+    ReqwestOtelSpan: AsyncReqwestOtelSpanBackend + Sync + Send + 'static,
+{
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if is_excluded(&self.excluded_urls, &req, extensions) {
+            return next.run(req, extensions).await;
+        }
+        let request_span = ReqwestOtelSpan::on_request_start(&req, extensions).await;
+        record_common_fields(&request_span, ReqwestOtelSpan::SPAN_KIND, &req, extensions);
+
+        let outcome_future = async {
+            let req = propagate_context(req, extensions);
+            ReqwestOtelSpan::on_request_headers_sent(&request_span, &req, extensions).await;
+
+            // Run the request
+            let outcome = next.run(req, extensions).await;
+            if self.log_on_error {
+                log_error_response(&request_span, &outcome, extensions);
+            }
+            ReqwestOtelSpan::on_request_end(&request_span, &outcome, extensions).await;
+            outcome
+        };
+
+        outcome_future.instrument(request_span.clone()).await
+    }
+}
+
+/// Whether the request must not be traced, an [`ExcludedUrls`] extension taking priority over the
+/// patterns of the middleware.
+fn is_excluded(excluded_urls: &ExcludedUrls, req: &Request, extensions: &Extensions) -> bool {
+    extensions
+        .get::<ExcludedUrls>()
+        .unwrap_or(excluded_urls)
+        .matches(req.url().path())
+}
+
+/// Records the fields set by the middleware itself rather than the span backend, so that they are
+/// recorded whatever the span backend, as long as its span declares them.
+fn record_common_fields(
+    request_span: &Span,
+    span_kind: &str,
+    req: &Request,
+    extensions: &mut Extensions,
+) {
+    // `reqwest_otel_span!` leaves the kind empty, the one set on the request takes priority.
+    let span_kind = match extensions.get::<OtelKind>() {
+        Some(OtelKind(kind)) => kind.as_ref(),
+        None => span_kind,
+    };
+    request_span.record(OTEL_KIND, span_kind);
+    if let Some(ResendCount(resend_count)) = extensions.get::<ResendCount>() {
+        if *resend_count > 0 {
+            request_span.record(HTTP_REQUEST_RESEND_COUNT, *resend_count);
+        }
+    }
+    record_url_template(req, extensions, request_span);
+    record_request_id(req, request_span);
+    record_peer_ip(extensions, request_span);
+}
+
+/// Emits a warning event in the request span if the response has a client error status.
+fn log_error_response(request_span: &Span, outcome: &Result<Response>, extensions: &Extensions) {
+    let response = match outcome {
+        Ok(response) if response.status().is_client_error() => response,
+        _ => return,
+    };
+    if let Err(err) = response.error_for_status_ref() {
+        let url_template = extensions
+            .get::<OtelUrlTemplate>()
+            .map(|OtelUrlTemplate(template)| template.as_str());
+        tracing::warn!(
+            parent: request_span,
+            http.response.status_code = response.status().as_u16(),
+            url.template = url_template,
+            error.message = %err.without_url(),
+            "request failed"
+        );
+    }
+}
+
+/// Adds tracing headers to the given request to propagate the OpenTelemetry context to downstream
+/// revivers of the request, unless disabled with [`DisableOtelPropagation`].
+///
+/// [`DisableOtelPropagation`]: crate::DisableOtelPropagation
+#[allow(unused_variables)]
+fn propagate_context(req: Request, extensions: &Extensions) -> Request {
+    #[cfg(any(
+        feature = "opentelemetry_0_20",
+        feature = "opentelemetry_0_21",
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27",
+    ))]
+    if extensions.get::<crate::DisableOtelPropagation>().is_none() {
+        // Spans added by downstream consumers will be part of the same trace.
+        return crate::otel::inject_opentelemetry_context_into_request(req, extensions);
+    }
+    req
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex, OnceLock};
+    use std::time::Duration;
+
+    use reqwest_middleware::ClientBuilder;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::Event;
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
+    use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::{default_on_request_end, reqwest_otel_span, SpanBackendWithTiming};
+
+    /// A mock KV store, mapping hosts to service names.
+    fn kv_store() -> &'static Mutex<HashMap<String, String>> {
+        static KV_STORE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+        KV_STORE.get_or_init(Default::default)
+    }
+
+    async fn lookup_service_name(host: &str) -> Option<String> {
+        tokio::task::yield_now().await;
+        kv_store().lock().unwrap().get(host).cloned()
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct ServiceName(String);
+
+    struct KvStoreSpanBackend;
+
+    #[async_trait::async_trait]
+    impl AsyncReqwestOtelSpanBackend for KvStoreSpanBackend {
+        async fn on_request_start(req: &Request, ext: &mut Extensions) -> Span {
+            let host = req.url().host_str().unwrap_or_default();
+            let service = lookup_service_name(host).await.unwrap_or_default();
+            ext.insert(ServiceName(service.clone()));
+            reqwest_otel_span!(name = "test", req, peer.service = %service)
+        }
+
+        async fn on_request_end(span: &Span, outcome: &Result<Response>, _: &mut Extensions) {
+            default_on_request_end(span, outcome)
+        }
+    }
+
+    #[tokio::test]
+    async fn async_tracing_middleware_uses_async_span_backend() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        kv_store()
+            .lock()
+            .unwrap()
+            .insert("127.0.0.1".to_string(), "mock-service".to_string());
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(AsyncTracingMiddleware::<KvStoreSpanBackend>::new())
+            .build();
+
+        let req = client.get(server.uri()).build().unwrap();
+        let mut extensions = Extensions::new();
+        let res = client
+            .execute_with_extensions(req, &mut extensions)
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            extensions.get::<ServiceName>(),
+            Some(&ServiceName("mock-service".to_string()))
+        );
+    }
+
+    /// Records the `http.client.duration` values recorded on spans.
+    #[derive(Clone, Default)]
+    struct DurationRecorder(Arc<Mutex<Vec<u64>>>);
+
+    impl Visit for DurationRecorder {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == crate::HTTP_CLIENT_DURATION {
+                self.0.lock().unwrap().push(value);
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: Subscriber> Layer<S> for DurationRecorder {
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn span_backend_with_timing_records_request_duration() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+            .mount(&server)
+            .await;
+
+        let recorder = DurationRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::<SpanBackendWithTiming>::new())
+            .build();
+        let res = client.get(server.uri()).send().await.unwrap();
+        assert_eq!(res.status(), 200);
+
+        let durations = recorder.0.lock().unwrap();
+        assert_eq!(durations.len(), 1);
+        assert!(
+            (100..5_000).contains(&durations[0]),
+            "unexpected duration: {}ms",
+            durations[0]
+        );
+    }
+
+    /// Records the fields of an event.
+    #[derive(Clone, Default)]
+    struct EventFieldsRecorder(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Visit for EventFieldsRecorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    /// Records the `url.template` field of spans.
+    #[derive(Clone, Default)]
+    struct UrlTemplateRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for UrlTemplateRecorder {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == crate::URL_TEMPLATE {
+                self.0.lock().unwrap().push(value.to_owned());
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: Subscriber> Layer<S> for UrlTemplateRecorder {
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    async fn url_templates(path_names: Option<crate::OtelPathNames>) -> Vec<String> {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let recorder = UrlTemplateRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let mut builder = ClientBuilder::new(reqwest::Client::new());
+        if let Some(path_names) = path_names {
+            builder = builder.with_init(reqwest_middleware::Extension(path_names));
+        }
+        let client = builder.with(TracingMiddleware::default()).build();
+        client
+            .get(format!("{}/payment/abc", server.uri()))
+            .send()
+            .await
+            .unwrap();
+
+        let templates = recorder.0.lock().unwrap().clone();
+        templates
+    }
+
+    #[tokio::test]
+    async fn url_template_is_recorded_from_otel_path_names() {
+        let path_names = crate::OtelPathNames::known_paths(["/payment/{paymentId}"]).unwrap();
+        assert_eq!(
+            url_templates(Some(path_names)).await,
+            ["/payment/{paymentId}"]
+        );
+    }
+
+    #[tokio::test]
+    async fn url_template_is_not_recorded_without_otel_path_names() {
+        assert!(url_templates(None).await.is_empty());
+    }
+
+    /// Records the string fields recorded on spans after their creation.
+    #[derive(Clone, Default)]
+    struct RecordedStrFields(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Visit for RecordedStrFields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_owned(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: Subscriber> Layer<S> for RecordedStrFields {
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn peer_addresses_are_recorded() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        // Nothing listens on port 1, the request fails before a response is received.
+        let unreachable = "http://127.0.0.1:1".to_string();
+
+        let peer_ip = "10.0.0.1".parse().unwrap();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(reqwest_middleware::Extension(crate::PeerIp(peer_ip)))
+            .with(TracingMiddleware::default())
+            .build();
+        for (url, expected) in [
+            (server.uri(), server.address().ip().to_string()),
+            (unreachable, "10.0.0.1".to_string()),
+        ] {
+            let recorder = RecordedStrFields::default();
+            let _guard =
+                tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+            let _ = client.get(url).send().await;
+
+            let fields = recorder.0.lock().unwrap();
+            assert_eq!(fields[crate::NETWORK_PEER_ADDRESS], expected);
+        }
+    }
+
+    async fn recorded_otel_kind(extension: Option<crate::OtelKind>) -> String {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let recorder = RecordedStrFields::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::default())
+            .build();
+        let mut request = client.get(server.uri());
+        if let Some(kind) = extension {
+            request = request.with_extension(kind);
+        }
+        request.send().await.unwrap();
+
+        let fields = recorder.0.lock().unwrap();
+        fields[OTEL_KIND].clone()
+    }
+
+    #[tokio::test]
+    async fn otel_kind_defaults_to_client() {
+        assert_eq!(recorded_otel_kind(None).await, "client");
+    }
+
+    #[tokio::test]
+    async fn otel_kind_extension_overrides_the_span_kind() {
+        let kind = crate::OtelKind("producer".into());
+        assert_eq!(recorded_otel_kind(Some(kind)).await, "producer");
+    }
+
+    #[tokio::test]
+    async fn successful_responses_record_an_ok_status() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let recorder = RecordedStrFields::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::default())
+            .build();
+        client.get(server.uri()).send().await.unwrap();
+
+        let fields = recorder.0.lock().unwrap();
+        assert_eq!(fields[crate::OTEL_STATUS_CODE], crate::OTEL_STATUS_OK);
+    }
+
+    #[tokio::test]
+    async fn failed_requests_record_the_error_type() {
+        let recorder = RecordedStrFields::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::default())
+            .build();
+        // Nothing listens on port 1, the connection is refused.
+        client.get("http://127.0.0.1:1").send().await.unwrap_err();
+        assert_eq!(
+            recorder.0.lock().unwrap()[crate::ERROR_TYPE],
+            "Reqwest(connect)"
+        );
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::default())
+            .with(reqwest_middleware::middleware_fn(|_, _, _| {
+                Box::pin(async {
+                    Err(reqwest_middleware::Error::middleware_boxed(
+                        "middleware failure".into(),
+                    ))
+                })
+            }))
+            .build();
+        client.get("http://127.0.0.1:1").send().await.unwrap_err();
+        assert_eq!(recorder.0.lock().unwrap()[crate::ERROR_TYPE], "Middleware");
+    }
+
+    #[tokio::test]
+    async fn span_backend_fn_delegates_to_the_closures() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let recorded_statuses = statuses.clone();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::with_span_backend_fn(
+                |req, ext| {
+                    ext.insert(OtelKind("internal".into()));
+                    crate::reqwest_otel_span!(name = "closure span", req)
+                },
+                move |span, outcome, _| {
+                    crate::default_on_request_end(span, outcome);
+                    let status = outcome.as_ref().unwrap().status();
+                    recorded_statuses.lock().unwrap().push(status.as_u16());
+                },
+            ))
+            .build();
+
+        let recorder = RecordedStrFields::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+        client.get(server.uri()).send().await.unwrap();
+
+        assert_eq!(*statuses.lock().unwrap(), [201]);
+        assert_eq!(recorder.0.lock().unwrap()[OTEL_KIND], "internal");
+    }
+
+    struct InternalSpanBackend;
+
+    impl ReqwestOtelSpanBackend for InternalSpanBackend {
+        const SPAN_KIND: &'static str = "internal";
+
+        fn on_request_start(req: &Request, _extension: &mut Extensions) -> Span {
+            reqwest_otel_span!(name = "internal", req)
+        }
+
+        fn on_request_end(span: &Span, outcome: &Result<Response>, _extension: &mut Extensions) {
+            default_on_request_end(span, outcome)
+        }
+    }
+
+    #[tokio::test]
+    async fn dynamic_span_backend_selects_the_backend_per_request() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let span_backend = DynamicSpanBackend::new(|req| -> &'static dyn ErasedSpanBackend {
+            if req.url().path() == "/internal" {
+                &InternalSpanBackend
+            } else {
+                &DefaultSpanBackend
+            }
+        });
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::with_dynamic_span_backend(span_backend))
+            .build();
+
+        for &(path, expected_kind) in &[("/internal", "internal"), ("/external", "client")] {
+            let recorder = RecordedStrFields::default();
+            let _guard =
+                tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+            client
+                .get(format!("{}{}", server.uri(), path))
+                .send()
+                .await
+                .unwrap();
+
+            let fields = recorder.0.lock().unwrap();
+            assert_eq!(fields[OTEL_KIND], expected_kind, "{}", path);
+            assert_eq!(fields[crate::OTEL_STATUS_CODE], crate::OTEL_STATUS_OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn request_id_header_is_recorded() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::default())
+            .build();
+
+        for &(request_id, expected) in &[(Some("req-123"), Some("req-123")), (None, None)] {
+            let recorder = RecordedStrFields::default();
+            let _guard =
+                tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+            let mut request = client.get(server.uri());
+            if let Some(request_id) = request_id {
+                request = request.header("x-request-id", request_id);
+            }
+            request.send().await.unwrap();
+
+            let fields = recorder.0.lock().unwrap();
+            assert_eq!(
+                fields.get(crate::HTTP_REQUEST_ID).map(String::as_str),
+                expected
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn request_derived_fields_are_recorded_with_any_span_backend() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let path_names = crate::OtelPathNames::known_paths(["/payment/{paymentId}"]).unwrap();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(reqwest_middleware::Extension(path_names))
+            .with(TracingMiddleware::with_span_backend_fn(
+                |req, _| reqwest_otel_span!(name = "closure span", req),
+                |span, outcome, _| default_on_request_end(span, outcome),
+            ))
+            .build();
+
+        let recorder = RecordedStrFields::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+        client
+            .get(format!("{}/payment/abc", server.uri()))
+            .header("x-request-id", "req-123")
+            .send()
+            .await
+            .unwrap();
+
+        let fields = recorder.0.lock().unwrap();
+        assert_eq!(fields[crate::HTTP_REQUEST_ID], "req-123");
+        assert_eq!(fields[crate::URL_TEMPLATE], "/payment/{paymentId}");
+    }
+
+    /// The hooks of [`LifecycleSpanBackend`] run for a request, in order.
+    #[derive(Clone, Debug, Default)]
+    struct LifecycleEvents(Vec<&'static str>);
+
+    struct LifecycleSpanBackend;
+
+    impl ReqwestOtelSpanBackend for LifecycleSpanBackend {
+        fn on_request_start(req: &Request, extension: &mut Extensions) -> Span {
+            extension.insert(LifecycleEvents(vec!["start"]));
+            reqwest_otel_span!(name = "lifecycle", req)
+        }
+
+        fn on_request_headers_sent(_span: &Span, _req: &Request, extension: &mut Extensions) {
+            extension
+                .get_mut::<LifecycleEvents>()
+                .unwrap()
+                .0
+                .push("headers_sent");
+        }
+
+        fn on_request_end(span: &Span, outcome: &Result<Response>, extension: &mut Extensions) {
+            extension
+                .get_mut::<LifecycleEvents>()
+                .unwrap()
+                .0
+                .push("end");
+            default_on_request_end(span, outcome)
+        }
+    }
+
+    struct AsyncLifecycleSpanBackend;
+
+    #[async_trait::async_trait]
+    impl AsyncReqwestOtelSpanBackend for AsyncLifecycleSpanBackend {
+        async fn on_request_start(req: &Request, extension: &mut Extensions) -> Span {
+            LifecycleSpanBackend::on_request_start(req, extension)
+        }
+
+        async fn on_request_headers_sent(span: &Span, req: &Request, extension: &mut Extensions) {
+            LifecycleSpanBackend::on_request_headers_sent(span, req, extension)
+        }
+
+        async fn on_request_end(
+            span: &Span,
+            outcome: &Result<Response>,
+            extension: &mut Extensions,
+        ) {
+            LifecycleSpanBackend::on_request_end(span, outcome, extension)
+        }
+    }
+
+    /// Sends a request with `middleware`, returning the hooks of the span backend that ran.
+    async fn lifecycle_events(middleware: impl Middleware) -> LifecycleEvents {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(middleware)
+            .build();
+        let mut extensions = Extensions::new();
+        let req = client.get(server.uri()).build().unwrap();
+        client
+            .execute_with_extensions(req, &mut extensions)
+            .await
+            .unwrap();
+
+        extensions.remove::<LifecycleEvents>().unwrap()
+    }
+
+    #[tokio::test]
+    async fn headers_sent_hook_runs_between_start_and_end() {
+        // The span backend implements neither `Default` nor `Clone`, the middleware is cloned.
+        let middleware = TracingMiddleware::<LifecycleSpanBackend>::new();
+        let events = lifecycle_events(middleware.clone()).await;
+        assert_eq!(events.0, ["start", "headers_sent", "end"]);
+    }
+
+    #[tokio::test]
+    async fn headers_sent_hook_runs_with_the_async_span_backend() {
+        let events =
+            lifecycle_events(AsyncTracingMiddleware::<AsyncLifecycleSpanBackend>::new()).await;
+        assert_eq!(events.0, ["start", "headers_sent", "end"]);
+    }
+
+    #[tokio::test]
+    async fn headers_sent_fn_runs_with_the_span_backend_fn() {
+        let middleware = TracingMiddleware::with_span_backend_fn(
+            LifecycleSpanBackend::on_request_start,
+            LifecycleSpanBackend::on_request_end,
+        )
+        .with_headers_sent_fn(LifecycleSpanBackend::on_request_headers_sent);
+        let events = lifecycle_events(middleware).await;
+        assert_eq!(events.0, ["start", "headers_sent", "end"]);
+    }
+
+    /// Records the name and value of `response header` events.
+    #[derive(Clone, Default)]
+    struct ResponseHeaderRecorder(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl<S: Subscriber> Layer<S> for ResponseHeaderRecorder {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut fields = EventFieldsRecorder::default();
+            event.record(&mut fields);
+            let fields = fields.0.lock().unwrap();
+            if fields.get("message").map(String::as_str) == Some("response header") {
+                let header = (
+                    fields["header.name"].clone(),
+                    fields["header.value"].clone(),
+                );
+                self.0.lock().unwrap().push(header);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn span_backend_with_response_headers_emits_the_captured_headers() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .append_header("x-request-id", "abc")
+                    .append_header("x-cache", "hit")
+                    .append_header("x-cache", "miss")
+                    .append_header("x-other", "ignored"),
+            )
+            .mount(&server)
+            .await;
+
+        let recorder = ResponseHeaderRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(reqwest_middleware::Extension(
+                crate::SpanBackendWithResponseHeaders(vec!["x-request-id", "x-cache", "x-missing"]),
+            ))
+            .with(TracingMiddleware::<crate::SpanBackendWithResponseHeaders>::new())
+            .build();
+        client.get(server.uri()).send().await.unwrap();
+
+        let headers = recorder.0.lock().unwrap();
+        let header = |name: &str, value: &str| (format!("{:?}", name), format!("{:?}", value));
+        assert_eq!(
+            *headers,
+            [
+                header("x-request-id", "abc"),
+                header("x-cache", "hit"),
+                header("x-cache", "miss"),
+            ]
+        );
+    }
+
+    /// Records the fields of `WARN` events.
+    #[derive(Clone, Default)]
+    struct WarnEventRecorder(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+    impl<S: Subscriber> Layer<S> for WarnEventRecorder {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == tracing::Level::WARN {
+                let mut fields = EventFieldsRecorder::default();
+                event.record(&mut fields);
+                let fields = fields.0.lock().unwrap().clone();
+                self.0.lock().unwrap().push(fields);
+            }
+        }
+    }
+
+    async fn warn_events(middleware: impl Middleware, status: u16) -> Vec<HashMap<String, String>> {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+
+        let recorder = WarnEventRecorder::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(recorder.clone()));
+
+        let path_names = crate::OtelPathNames::known_paths(["/payment/{paymentId}"]).unwrap();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(reqwest_middleware::Extension(path_names))
+            .with(middleware)
+            .build();
+        client
+            .get(format!("{}/payment/abc", server.uri()))
+            .send()
+            .await
+            .unwrap();
+
+        let events = recorder.0.lock().unwrap().clone();
+        events
+    }
+
+    #[tokio::test]
+    async fn error_responses_are_not_logged_by_default() {
+        let events = warn_events(TracingMiddleware::default(), 404).await;
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn error_responses_are_logged_when_enabled() {
+        let events = warn_events(TracingMiddleware::default().with_log_on_error(true), 404).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["message"], "request failed");
+        assert_eq!(events[0]["http.response.status_code"], "404");
+        assert_eq!(events[0]["url.template"], "\"/payment/{paymentId}\"");
+        assert!(!events[0].contains_key("url.full"));
+        assert_eq!(
+            events[0]["error.message"],
+            "HTTP status client error (404 Not Found)"
+        );
+    }
+
+    #[tokio::test]
+    async fn error_responses_are_logged_by_the_async_middleware() {
+        let middleware =
+            AsyncTracingMiddleware::<KvStoreSpanBackend>::new().with_log_on_error(true);
+        let events = warn_events(middleware, 404).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["http.response.status_code"], "404");
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_not_logged() {
+        let events = warn_events(TracingMiddleware::default().with_log_on_error(true), 503).await;
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn successful_responses_are_not_logged() {
+        let events = warn_events(TracingMiddleware::default().with_log_on_error(true), 200).await;
+        assert!(events.is_empty());
+    }
+
+    /// Counts the spans created.
+    #[derive(Clone, Default)]
+    struct SpanCounter(Arc<Mutex<usize>>);
+
+    impl<S: Subscriber> Layer<S> for SpanCounter {
+        fn on_new_span(&self, _attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    async fn spans_created(
+        middleware: impl Middleware,
+        path: &str,
+        extension: Option<ExcludedUrls>,
+    ) -> usize {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(middleware)
+            .build();
+
+        let counter = SpanCounter::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(counter.clone()));
+        let mut request = client.get(format!("{}{}", server.uri(), path));
+        if let Some(extension) = extension {
+            request = request.with_extension(extension);
+        }
+        let res = request.send().await.unwrap();
+        assert_eq!(res.status(), 200);
+
+        let count = *counter.0.lock().unwrap();
+        count
+    }
+
+    fn excluding_health() -> TracingMiddleware<DefaultSpanBackend> {
+        TracingMiddleware::default().with_excluded_urls(["/health"])
+    }
+
+    #[tokio::test]
+    async fn excluded_urls_are_not_traced() {
+        assert_eq!(spans_created(excluding_health(), "/health", None).await, 0);
+        assert_eq!(spans_created(excluding_health(), "/payment", None).await, 1);
+    }
+
+    #[tokio::test]
+    async fn excluded_urls_extension_takes_priority() {
+        let metrics = ExcludedUrls::new(["/metrics"]);
+        assert_eq!(
+            spans_created(excluding_health(), "/metrics", Some(metrics.clone())).await,
+            0
+        );
+        assert_eq!(
+            spans_created(excluding_health(), "/health", Some(metrics)).await,
+            1
+        );
+        assert_eq!(
+            spans_created(excluding_health(), "/health", Some(ExcludedUrls::default())).await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn excluded_urls_are_not_traced_by_the_async_middleware() {
+        let middleware =
+            || AsyncTracingMiddleware::<KvStoreSpanBackend>::new().with_excluded_urls(["/health"]);
+        assert_eq!(spans_created(middleware(), "/health", None).await, 0);
+        assert_eq!(spans_created(middleware(), "/payment", None).await, 1);
+    }
+}
@@ -1,64 +1,158 @@
-use reqwest::header::{HeaderName, HeaderValue};
-use reqwest::Request;
+use http::Extensions;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Request, Response};
 use std::str::FromStr;
 use tracing::Span;
 
+use crate::baggage::BaggageContext;
+
 /// Injects the given OpenTelemetry Context into a reqwest::Request headers to allow propagation downstream.
-pub fn inject_opentelemetry_context_into_request(mut request: Request) -> Request {
+pub fn inject_opentelemetry_context_into_request(
+    mut request: Request,
+    extensions: &Extensions,
+) -> Request {
     #[cfg(feature = "opentelemetry_0_20")]
     opentelemetry_0_20_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_20_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_21_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_21")]
     opentelemetry_0_21_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_21_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_22_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_22")]
     opentelemetry_0_22_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_22_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_23_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_23")]
     opentelemetry_0_23_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_23_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_24_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_24")]
     opentelemetry_0_24_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_24_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_25_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_25")]
     opentelemetry_0_25_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_25_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_26_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_26")]
     opentelemetry_0_26_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_26_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_27_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     #[cfg(feature = "opentelemetry_0_27")]
     opentelemetry_0_27_pkg::global::get_text_map_propagator(|injector| {
+        use opentelemetry_0_27_pkg::baggage::{BaggageExt, KeyValueMetadata};
         use tracing_opentelemetry_0_28_pkg::OpenTelemetrySpanExt;
-        let context = Span::current().context();
-        injector.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        let mut context = Span::current().context();
+        if let Some(baggage) = extensions.get::<BaggageContext>() {
+            context = context.with_baggage(
+                baggage
+                    .entries()
+                    .map(|(name, value, metadata)| KeyValueMetadata::new(name, value, metadata)),
+            );
+        }
+        injector.inject_context(
+            &context,
+            &mut RequestCarrier::with_extensions(&mut request, extensions),
+        )
     });
 
     request
@@ -70,18 +164,59 @@ pub fn inject_opentelemetry_context_into_request(mut request: Request) -> Reques
 /// This will allow the propagator to inject opentelemetry context into a standard data structure. Will basically
 /// insert a "traceparent" string value "{version}-{trace_id}-{span_id}-{trace-flags}" of the spans context into the headers.
 /// Listeners can then re-hydrate the context to add additional spans to the same trace.
-struct RequestCarrier<'a> {
+///
+/// It implements the `Injector` trait of every enabled `opentelemetry` version, so custom
+/// propagation code can pass `&mut RequestCarrier::new(&mut request)` to
+/// `TextMapPropagator::inject_context` like the middleware does.
+///
+/// When used by [`TracingMiddleware`], headers listed in an [`OtelHeaderPropagationFilter`]
+/// extension are not inserted.
+///
+/// [`OtelHeaderPropagationFilter`]: crate::OtelHeaderPropagationFilter
+/// [`TracingMiddleware`]: crate::TracingMiddleware
+pub struct RequestCarrier<'a> {
     request: &'a mut Request,
+    excluded_headers: &'a [String],
 }
 
 impl<'a> RequestCarrier<'a> {
+    /// Create a carrier inserting headers into the given request.
     pub fn new(request: &'a mut Request) -> Self {
-        RequestCarrier { request }
+        RequestCarrier {
+            request,
+            excluded_headers: &[],
+        }
+    }
+
+    /// Create a carrier skipping the headers listed in an [`OtelHeaderPropagationFilter`]
+    /// extension.
+    ///
+    /// [`OtelHeaderPropagationFilter`]: crate::OtelHeaderPropagationFilter
+    pub(crate) fn with_extensions(request: &'a mut Request, extensions: &'a Extensions) -> Self {
+        let excluded_headers = extensions
+            .get::<crate::OtelHeaderPropagationFilter>()
+            .map_or(&[][..], |filter| &filter.0);
+        RequestCarrier {
+            request,
+            excluded_headers,
+        }
+    }
+
+    /// Returns a mutable reference to the headers of the request.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        self.request.headers_mut()
     }
 }
 
 impl<'a> RequestCarrier<'a> {
     fn set_inner(&mut self, key: &str, value: String) {
+        if self
+            .excluded_headers
+            .iter()
+            .any(|excluded| excluded.eq_ignore_ascii_case(key))
+        {
+            return;
+        }
         let header_name = HeaderName::from_str(key).expect("Must be header name");
         let header_value = HeaderValue::from_str(&value).expect("Must be a header value");
         self.request.headers_mut().insert(header_name, header_value);
@@ -144,19 +279,378 @@ impl<'a> opentelemetry_0_27_pkg::propagation::Injector for RequestCarrier<'a> {
     }
 }
 
+/// Extracts the OpenTelemetry context propagated in the headers of an incoming request.
+///
+/// This allows continuing the trace of an incoming request in the outgoing requests made while
+/// handling it: set the extracted context as parent of the span handling the request, e.g. with
+/// `tracing_opentelemetry::OpenTelemetrySpanExt::set_parent`, and the spans of
+/// [`TracingMiddleware`](crate::TracingMiddleware) become its children.
+///
+/// The context is extracted with the global text map propagator, using the most recent
+/// `opentelemetry` version enabled.
+///
+/// ```no_run
+/// # use reqwest_tracing::TraceContextExtractor;
+/// # let incoming_headers = http::HeaderMap::new();
+/// let context = TraceContextExtractor::extract_from_headers(&incoming_headers);
+/// ```
+pub struct TraceContextExtractor;
+
+#[cfg(all(
+    feature = "opentelemetry_0_20",
+    not(any(
+        feature = "opentelemetry_0_21",
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_20_pkg::Context {
+        opentelemetry_0_20_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(all(
+    feature = "opentelemetry_0_21",
+    not(any(
+        feature = "opentelemetry_0_22",
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_21_pkg::Context {
+        opentelemetry_0_21_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(all(
+    feature = "opentelemetry_0_22",
+    not(any(
+        feature = "opentelemetry_0_23",
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_22_pkg::Context {
+        opentelemetry_0_22_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(all(
+    feature = "opentelemetry_0_23",
+    not(any(
+        feature = "opentelemetry_0_24",
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_23_pkg::Context {
+        opentelemetry_0_23_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(all(
+    feature = "opentelemetry_0_24",
+    not(any(
+        feature = "opentelemetry_0_25",
+        feature = "opentelemetry_0_26",
+        feature = "opentelemetry_0_27"
+    ))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_24_pkg::Context {
+        opentelemetry_0_24_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(all(
+    feature = "opentelemetry_0_25",
+    not(any(feature = "opentelemetry_0_26", feature = "opentelemetry_0_27"))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_25_pkg::Context {
+        opentelemetry_0_25_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(all(
+    feature = "opentelemetry_0_26",
+    not(any(feature = "opentelemetry_0_27"))
+))]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_26_pkg::Context {
+        opentelemetry_0_26_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_27")]
+impl TraceContextExtractor {
+    /// Extracts the OpenTelemetry context from the given headers.
+    pub fn extract_from_headers(headers: &HeaderMap) -> opentelemetry_0_27_pkg::Context {
+        opentelemetry_0_27_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        })
+    }
+}
+
+/// Extractor used via opentelemetry propagator to read the context from headers, the counterpart
+/// of [`RequestCarrier`].
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl<'a> HeaderExtractor<'a> {
+    fn get_inner(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys_inner(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_20")]
+impl<'a> opentelemetry_0_20_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_21")]
+impl<'a> opentelemetry_0_21_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_22")]
+impl<'a> opentelemetry_0_22_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_23")]
+impl<'a> opentelemetry_0_23_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_24")]
+impl<'a> opentelemetry_0_24_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_25")]
+impl<'a> opentelemetry_0_25_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_26")]
+impl<'a> opentelemetry_0_26_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_27")]
+impl<'a> opentelemetry_0_27_pkg::propagation::Extractor for HeaderExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.keys_inner()
+    }
+}
+
+/// Extractor reading the OpenTelemetry context from the headers of a [`Response`].
+///
+/// Useful when the server echoes the context it received, e.g. to check in tests that it was
+/// propagated correctly. Like [`RequestCarrier`], it implements the `Extractor` trait of every
+/// enabled `opentelemetry` version, to be passed to `TextMapPropagator::extract`.
+pub struct ResponseExtractor<'a>(HeaderExtractor<'a>);
+
+impl<'a> ResponseExtractor<'a> {
+    /// Create an extractor reading the headers of the given response.
+    pub fn new(response: &'a Response) -> Self {
+        ResponseExtractor(HeaderExtractor(response.headers()))
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_20")]
+impl<'a> opentelemetry_0_20_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_21")]
+impl<'a> opentelemetry_0_21_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_22")]
+impl<'a> opentelemetry_0_22_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_23")]
+impl<'a> opentelemetry_0_23_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_24")]
+impl<'a> opentelemetry_0_24_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_25")]
+impl<'a> opentelemetry_0_25_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_26")]
+impl<'a> opentelemetry_0_26_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
+#[cfg(feature = "opentelemetry_0_27")]
+impl<'a> opentelemetry_0_27_pkg::propagation::Extractor for ResponseExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_inner(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys_inner()
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::sync::OnceLock;
-
-    use crate::{DisableOtelPropagation, TracingMiddleware};
-    use reqwest::Response;
-    use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Extension};
-    use tracing::{info_span, Instrument, Level};
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    use super::TraceContextExtractor;
+    use crate::baggage::{extract_baggage_from_headers, inject_baggage_into_extensions};
+    use crate::{
+        default_on_request_end, reqwest_otel_span, DisableOtelPropagation,
+        OtelHeaderPropagationFilter, ReqwestOtelSpanBackend, TracingMiddleware,
+    };
+    use http::Extensions;
+    use reqwest::header::HeaderMap;
+    use reqwest::{Request, Response};
+    use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Extension, Result};
+    use tracing::{info_span, Instrument, Level, Span};
 
     use tracing_subscriber::{filter, layer::SubscriberExt, Registry};
     use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
 
-    async fn make_echo_request_in_otel_context(client: ClientWithMiddleware) -> Response {
+    fn init_telemetry() {
         static TELEMETRY: OnceLock<()> = OnceLock::new();
 
         TELEMETRY.get_or_init(|| {
@@ -180,7 +674,16 @@ mod test {
                 let tracer = provider.versioned_tracer("reqwest", None::<&str>, None::<&str>, None);
                 let _ = opentelemetry_0_20_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_20_pkg::global::set_text_map_propagator(
-                    opentelemetry_0_20_pkg::sdk::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_20_pkg::sdk::propagation::TextMapCompositePropagator::new(
+                        vec![
+                        Box::new(
+                            opentelemetry_0_20_pkg::sdk::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(
+                            opentelemetry_0_20_pkg::sdk::propagation::BaggagePropagator::new(),
+                        ),
+                    ],
+                    ),
                 );
 
                 let telemetry = tracing_opentelemetry_0_21_pkg::layer().with_tracer(tracer);
@@ -203,7 +706,12 @@ mod test {
                 let tracer = provider.versioned_tracer("reqwest", None::<&str>, None::<&str>, None);
                 let _ = opentelemetry_0_21_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_21_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_21::propagation::TraceContextPropagator::new(),
+                    opentelemetry_sdk_0_21::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_21::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_21::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_22_pkg::layer().with_tracer(tracer);
@@ -226,7 +734,12 @@ mod test {
                 let tracer = provider.versioned_tracer("reqwest", None::<&str>, None::<&str>, None);
                 let _ = opentelemetry_0_22_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_22_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_22::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_22_pkg::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_22::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_22::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_23_pkg::layer().with_tracer(tracer);
@@ -249,7 +762,12 @@ mod test {
                 let tracer = provider.tracer_builder("reqwest").build();
                 let _ = opentelemetry_0_23_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_23_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_23::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_23_pkg::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_23::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_23::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_24_pkg::layer().with_tracer(tracer);
@@ -272,7 +790,12 @@ mod test {
                 let tracer = provider.tracer_builder("reqwest").build();
                 let _ = opentelemetry_0_24_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_24_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_24::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_24_pkg::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_24::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_24::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_25_pkg::layer().with_tracer(tracer);
@@ -288,7 +811,12 @@ mod test {
                 let tracer = provider.tracer_builder("reqwest").build();
                 let _ = opentelemetry_0_25_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_25_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_25::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_25_pkg::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_25::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_25::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_26_pkg::layer().with_tracer(tracer);
@@ -304,7 +832,12 @@ mod test {
                 let tracer = provider.tracer_builder("reqwest").build();
                 let _ = opentelemetry_0_26_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_26_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_26::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_26_pkg::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_26::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_26::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_27_pkg::layer().with_tracer(tracer);
@@ -320,7 +853,12 @@ mod test {
                 let tracer = provider.tracer("reqwest");
                 let _ = opentelemetry_0_27_pkg::global::set_tracer_provider(provider);
                 opentelemetry_0_27_pkg::global::set_text_map_propagator(
-                    opentelemetry_sdk_0_27::propagation::TraceContextPropagator::new(),
+                    opentelemetry_0_27_pkg::propagation::TextMapCompositePropagator::new(vec![
+                        Box::new(
+                            opentelemetry_sdk_0_27::propagation::TraceContextPropagator::new(),
+                        ),
+                        Box::new(opentelemetry_sdk_0_27::propagation::BaggagePropagator::new()),
+                    ]),
                 );
 
                 let telemetry = tracing_opentelemetry_0_28_pkg::layer().with_tracer(tracer);
@@ -329,6 +867,227 @@ mod test {
 
             tracing::subscriber::set_global_default(subscriber).unwrap();
         });
+    }
+
+    /// Returns the OpenTelemetry `(trace_id, span_id)` of the given span.
+    fn otel_ids(span: &Span) -> (String, String) {
+        #[cfg(all(
+            feature = "opentelemetry_0_20",
+            not(any(
+                feature = "opentelemetry_0_21",
+                feature = "opentelemetry_0_22",
+                feature = "opentelemetry_0_23",
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_20_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_21_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(all(
+            feature = "opentelemetry_0_21",
+            not(any(
+                feature = "opentelemetry_0_22",
+                feature = "opentelemetry_0_23",
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_21_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_22_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(all(
+            feature = "opentelemetry_0_22",
+            not(any(
+                feature = "opentelemetry_0_23",
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_22_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_23_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(all(
+            feature = "opentelemetry_0_23",
+            not(any(
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_23_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_24_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(all(
+            feature = "opentelemetry_0_24",
+            not(any(
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_24_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_25_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(all(
+            feature = "opentelemetry_0_25",
+            not(any(feature = "opentelemetry_0_26", feature = "opentelemetry_0_27"))
+        ))]
+        let span_context = {
+            use opentelemetry_0_25_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_26_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(all(
+            feature = "opentelemetry_0_26",
+            not(any(feature = "opentelemetry_0_27"))
+        ))]
+        let span_context = {
+            use opentelemetry_0_26_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_27_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        #[cfg(feature = "opentelemetry_0_27")]
+        let span_context = {
+            use opentelemetry_0_27_pkg::trace::TraceContextExt;
+            use tracing_opentelemetry_0_28_pkg::OpenTelemetrySpanExt;
+            span.context().span().span_context().clone()
+        };
+
+        (
+            span_context.trace_id().to_string(),
+            span_context.span_id().to_string(),
+        )
+    }
+
+    /// Extracts the context from the given headers, returning the OpenTelemetry
+    /// `(trace_id, span_id)` of its span.
+    fn extracted_ids(headers: &HeaderMap) -> (String, String) {
+        let context = TraceContextExtractor::extract_from_headers(headers);
+        #[cfg(all(
+            feature = "opentelemetry_0_20",
+            not(any(
+                feature = "opentelemetry_0_21",
+                feature = "opentelemetry_0_22",
+                feature = "opentelemetry_0_23",
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_20_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(all(
+            feature = "opentelemetry_0_21",
+            not(any(
+                feature = "opentelemetry_0_22",
+                feature = "opentelemetry_0_23",
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_21_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(all(
+            feature = "opentelemetry_0_22",
+            not(any(
+                feature = "opentelemetry_0_23",
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_22_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(all(
+            feature = "opentelemetry_0_23",
+            not(any(
+                feature = "opentelemetry_0_24",
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_23_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(all(
+            feature = "opentelemetry_0_24",
+            not(any(
+                feature = "opentelemetry_0_25",
+                feature = "opentelemetry_0_26",
+                feature = "opentelemetry_0_27"
+            ))
+        ))]
+        let span_context = {
+            use opentelemetry_0_24_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(all(
+            feature = "opentelemetry_0_25",
+            not(any(feature = "opentelemetry_0_26", feature = "opentelemetry_0_27"))
+        ))]
+        let span_context = {
+            use opentelemetry_0_25_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(all(
+            feature = "opentelemetry_0_26",
+            not(any(feature = "opentelemetry_0_27"))
+        ))]
+        let span_context = {
+            use opentelemetry_0_26_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+        #[cfg(feature = "opentelemetry_0_27")]
+        let span_context = {
+            use opentelemetry_0_27_pkg::trace::TraceContextExt;
+            context.span().span_context().clone()
+        };
+
+        (
+            span_context.trace_id().to_string(),
+            span_context.span_id().to_string(),
+        )
+    }
+
+    async fn make_echo_request_in_otel_context(client: ClientWithMiddleware) -> Response {
+        init_telemetry();
 
         // Mock server - sends all request headers back in the response
         let server = MockServer::start().await;
@@ -379,4 +1138,210 @@ mod test {
             "request should not contain traceparent if context propagation is disabled"
         );
     }
+
+    /// Stores the OpenTelemetry ids of the client span created by [`RecordingSpanBackend`].
+    #[derive(Clone, Default)]
+    struct ClientSpanIds(Arc<Mutex<Option<(String, String)>>>);
+
+    /// Creates enabled client spans, recording their ids in [`ClientSpanIds`].
+    struct RecordingSpanBackend;
+
+    impl ReqwestOtelSpanBackend for RecordingSpanBackend {
+        fn on_request_start(req: &Request, ext: &mut Extensions) -> Span {
+            let span = reqwest_otel_span!(name = "downstream-call", req);
+            if let Some(ids) = ext.get::<ClientSpanIds>() {
+                *ids.0.lock().unwrap() = Some(otel_ids(&span));
+            }
+            span
+        }
+
+        fn on_request_end(span: &Span, outcome: &Result<Response>, _: &mut Extensions) {
+            default_on_request_end(span, outcome)
+        }
+    }
+
+    #[tokio::test]
+    async fn downstream_spans_are_children_of_the_client_span() {
+        init_telemetry();
+
+        // Downstream service - extracts the remote parent from the `traceparent` header, as it
+        // would when starting its own server span, and sends its ids back.
+        let downstream = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(|req: &wiremock::Request| {
+                let traceparent = req
+                    .headers
+                    .get("traceparent")
+                    .expect("traceparent must be propagated")
+                    .to_str()
+                    .unwrap();
+                let parts: Vec<&str> = traceparent.split('-').collect();
+                ResponseTemplate::new(200)
+                    .append_header("x-trace-id", parts[1])
+                    .append_header("x-parent-span-id", parts[2])
+            })
+            .mount(&downstream)
+            .await;
+
+        let client_span_ids = ClientSpanIds::default();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(Extension(client_span_ids.clone()))
+            .with(TracingMiddleware::<RecordingSpanBackend>::new())
+            .build();
+
+        let root = info_span!("root");
+        let (root_trace_id, root_span_id) = otel_ids(&root);
+        let resp = client
+            .get(downstream.uri())
+            .send()
+            .instrument(root)
+            .await
+            .unwrap();
+
+        let (client_trace_id, client_span_id) = client_span_ids.0.lock().unwrap().clone().unwrap();
+        let header = |name: &str| resp.headers()[name].to_str().unwrap().to_owned();
+
+        assert_eq!(client_trace_id, root_trace_id);
+        assert_ne!(
+            client_span_id, root_span_id,
+            "the middleware must create its own span"
+        );
+        assert_eq!(header("x-trace-id"), client_trace_id);
+        assert_eq!(
+            header("x-parent-span-id"),
+            client_span_id,
+            "downstream spans must be children of the client span"
+        );
+    }
+
+    #[tokio::test]
+    async fn trace_context_extractor_reads_propagated_context() {
+        init_telemetry();
+
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(|req: &wiremock::Request| {
+                // Incoming headers are echoed back, so the test can extract them.
+                req.headers
+                    .iter()
+                    .fold(ResponseTemplate::new(200), |resp, (k, v)| {
+                        resp.append_header(k.clone(), v.clone())
+                    })
+            })
+            .mount(&server)
+            .await;
+
+        let client_span_ids = ClientSpanIds::default();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(Extension(client_span_ids.clone()))
+            .with(TracingMiddleware::<RecordingSpanBackend>::new())
+            .build();
+
+        let resp = client
+            .get(server.uri())
+            .send()
+            .instrument(info_span!("root"))
+            .await
+            .unwrap();
+
+        let client_ids = client_span_ids.0.lock().unwrap().clone().unwrap();
+        assert_eq!(extracted_ids(resp.headers()), client_ids);
+        assert_eq!(
+            extracted_ids(&HeaderMap::new()).0,
+            "00000000000000000000000000000000",
+            "no context is extracted without headers"
+        );
+    }
+
+    #[tokio::test]
+    async fn extracted_baggage_is_propagated() {
+        init_telemetry();
+
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(|req: &wiremock::Request| {
+                let baggage = req.headers.get("baggage").cloned();
+                baggage
+                    .into_iter()
+                    .fold(ResponseTemplate::new(200), |resp, value| {
+                        resp.append_header("baggage", value)
+                    })
+            })
+            .mount(&server)
+            .await;
+
+        let mut incoming_headers = HeaderMap::new();
+        incoming_headers.insert("baggage", "user=alice".parse().unwrap());
+        let baggage = extract_baggage_from_headers(&incoming_headers);
+        assert_eq!(baggage.get("user"), Some("alice"));
+        assert!(extract_baggage_from_headers(&HeaderMap::new()).is_empty());
+
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(TracingMiddleware::default())
+            .build();
+        let request = client.get(server.uri()).build().unwrap();
+        let mut extensions = Extensions::new();
+        inject_baggage_into_extensions(baggage, &mut extensions);
+        let resp = client
+            .execute_with_extensions(request, &mut extensions)
+            .instrument(info_span!("root"))
+            .await
+            .unwrap();
+
+        assert_eq!(resp.headers()["baggage"], "user=alice");
+    }
+
+    #[tokio::test]
+    async fn filtered_headers_are_not_propagated() {
+        let mut incoming_headers = HeaderMap::new();
+        incoming_headers.insert("baggage", "user=alice".parse().unwrap());
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(Extension(extract_baggage_from_headers(&incoming_headers)))
+            .with_init(Extension(OtelHeaderPropagationFilter(vec![
+                "Baggage".to_owned()
+            ])))
+            .with(TracingMiddleware::default())
+            .build();
+
+        let resp = make_echo_request_in_otel_context(client).await;
+
+        assert!(resp.headers().contains_key("traceparent"));
+        assert!(
+            !resp.headers().contains_key("baggage"),
+            "filtered headers should not be propagated"
+        );
+    }
+
+    #[cfg(feature = "opentelemetry_0_27")]
+    #[tokio::test]
+    async fn response_extractor_reads_the_echoed_context() {
+        use super::{RequestCarrier, ResponseExtractor};
+        use opentelemetry_0_27_pkg::trace::TraceContextExt;
+
+        let client_span_ids = ClientSpanIds::default();
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with_init(Extension(client_span_ids.clone()))
+            .with(TracingMiddleware::<RecordingSpanBackend>::new())
+            .build();
+        let resp = make_echo_request_in_otel_context(client).await;
+
+        let context = opentelemetry_0_27_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&ResponseExtractor::new(&resp))
+        });
+        let span_context = context.span().span_context().clone();
+        let ids = (
+            span_context.trace_id().to_string(),
+            span_context.span_id().to_string(),
+        );
+        assert_eq!(ids, client_span_ids.0.lock().unwrap().clone().unwrap());
+
+        let mut request = Request::new(reqwest::Method::GET, "http://localhost".parse().unwrap());
+        opentelemetry_0_27_pkg::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut RequestCarrier::new(&mut request))
+        });
+        assert_eq!(
+            request.headers()["traceparent"],
+            resp.headers()["traceparent"]
+        );
+    }
 }
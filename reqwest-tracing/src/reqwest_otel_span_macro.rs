@@ -14,6 +14,15 @@
 /// - http.response.status_code
 /// - error.message
 /// - error.cause_chain
+/// - error.type
+/// - http.request.resend_count
+/// - http.client.duration
+/// - url.template
+/// - http.request.id
+/// - network.peer.address
+/// - network.peer.port
+/// - otel.library.name
+/// - otel.library.version
 ///
 /// Here are some convenient functions to checkout [`default_on_request_success`], [`default_on_request_failure`],
 /// and [`default_on_request_end`].
@@ -59,7 +68,7 @@
 ///
 /// You can define new fields following the same syntax of [`tracing::info_span!`] for fields:
 ///
-/// ```rust,should_panic
+/// ```rust,no_run
 /// use reqwest_tracing::reqwest_otel_span;
 /// # let request: &reqwest::Request = todo!();
 ///
@@ -74,13 +83,55 @@
 /// let app_id = "XYZ";
 /// reqwest_otel_span!(name = "reqwest-http-request", request, app_id);
 ///
+/// // Define fields using the `tracing::field::display` and `tracing::field::debug` wrappers,
+/// // equivalent to the `%` and `?` sigils.
+/// reqwest_otel_span!(name = "reqwest-http-request", request, app_id = tracing::field::display(app_id));
+///
 /// // All together
 /// reqwest_otel_span!(name = "reqwest-http-request", request, time_elapsed = tracing::field::Empty, name = "AppName", app_id);
 /// ```
 ///
+/// The values of the fields can be one of the following bindings, computed once by the macro:
+/// - `url_path`: the path of the request URL, e.g. `/payment/123`.
+///
+/// The request itself is available under the name it was passed with. These bindings shadow
+/// variables of the caller with the same name: rename a `url_path` variable to use it as the
+/// value of a field.
+///
+/// ```rust,no_run
+/// use reqwest_tracing::reqwest_otel_span;
+/// # let request: &reqwest::Request = todo!();
+///
+/// reqwest_otel_span!(name = "reqwest-http-request", request, my_path = url_path);
+/// reqwest_otel_span!(name = "reqwest-http-request", request, my_path = %url_path);
+/// reqwest_otel_span!(name = "reqwest-http-request", request, url_path);
+/// ```
+///
+/// The default fields are reserved: passing one of them again, e.g. `server.port = 8080`, is a
+/// compile error. So are the fields added by the `deprecated_attributes` feature, e.g.
+/// `http.method`, whether it is enabled or not. Record a new value on the returned span instead, or use
+/// [`ReqwestOtelSpanBackend::SPAN_KIND`] and [`OtelKind`] to change `otel.kind`.
+///
+/// `otel.kind` is left empty by the macro and recorded by the tracing middleware, so spans created
+/// outside of it have to record it themselves.
+///
+/// ```rust,compile_fail
+/// use reqwest_tracing::reqwest_otel_span;
+/// # let request: &reqwest::Request = todo!();
+///
+/// reqwest_otel_span!(name = "reqwest-http-request", request, server.port = 8080);
+/// ```
+///
+/// ```rust,compile_fail
+/// use reqwest_tracing::reqwest_otel_span;
+/// # let request: &reqwest::Request = todo!();
+///
+/// reqwest_otel_span!(name = "reqwest-http-request", request, http.method = "GET");
+/// ```
+///
 /// You can also choose to customise the level of the generated span:
 ///
-/// ```rust,should_panic
+/// ```rust,no_run
 /// use reqwest_tracing::reqwest_otel_span;
 /// use tracing::Level;
 /// # let request: &reqwest::Request = todo!();
@@ -92,17 +143,27 @@
 ///     Level::INFO
 /// };
 ///
-/// // `level =` and name MUST come before the request, in this order
+/// // `level =` and `name =` MUST come before the request, in any order
 /// reqwest_otel_span!(level = level, name = "reqwest-http-request", request);
+/// reqwest_otel_span!(name = "reqwest-http-request", level = level, request);
 /// ```
 ///
 ///
 /// [`DefaultSpanBackend`]: crate::reqwest_otel_span_builder::DefaultSpanBackend
+/// [`ReqwestOtelSpanBackend::SPAN_KIND`]: crate::ReqwestOtelSpanBackend::SPAN_KIND
+/// [`OtelKind`]: crate::OtelKind
 /// [`SpanBackendWithUrl`]: crate::reqwest_otel_span_builder::DefaultSpanBackend
 /// [`default_on_request_success`]: crate::reqwest_otel_span_builder::default_on_request_success
 /// [`default_on_request_failure`]: crate::reqwest_otel_span_builder::default_on_request_failure
 /// [`default_on_request_end`]: crate::reqwest_otel_span_builder::default_on_request_end
 macro_rules! reqwest_otel_span {
+    // Custom level given after the name, normalised to the level-first arms below
+    (name=$name:expr, level=$level:expr, $request:ident) => {
+        reqwest_otel_span!(level=$level, name=$name, $request,)
+    };
+    (name=$name:expr, level=$level:expr, $request:ident, $($field:tt)*) => {
+        reqwest_otel_span!(level=$level, name=$name, $request, $($field)*)
+    };
     // Vanilla root span at default INFO level, with no additional fields
     (name=$name:expr, $request:ident) => {
         reqwest_otel_span!(name=$name, $request,)
@@ -117,9 +178,18 @@ macro_rules! reqwest_otel_span {
     };
     // Root span with additional fields and custom level
     (level=$level:expr, name=$name:expr, $request:ident, $($field:tt)*) => {
+        {
+            $crate::__reqwest_otel_span_check_fields!($($field)*);
+            $crate::__reqwest_otel_span_bind_fields!(@find [$level, $name, $request, $($field)*] $($field)*)
+        }
+    };
+    // Internal arm, `$url_path` is the identifier the `url_path` binding is declared with
+    (@bound $url_path:ident, level=$level:expr, name=$name:expr, $request:ident, $($field:tt)*) => {
         {
             let method = $request.method();
             let url = $request.url();
+            #[allow(unused_variables)]
+            let $url_path = url.path();
             let scheme = url.scheme();
             let host = url.host_str().unwrap_or("");
             let host_port = url.port_or_known_default().unwrap_or(0) as i64;
@@ -138,12 +208,21 @@ macro_rules! reqwest_otel_span {
                         server.address = %host,
                         server.port = %host_port,
                         user_agent.original = %user_agent,
-                        otel.kind = "client",
+                        otel.kind = tracing::field::Empty,
                         otel.name = %otel_name,
                         otel.status_code = tracing::field::Empty,
                         http.response.status_code = tracing::field::Empty,
                         error.message = tracing::field::Empty,
                         error.cause_chain = tracing::field::Empty,
+                        error.type = tracing::field::Empty,
+                        http.request.resend_count = tracing::field::Empty,
+                        http.client.duration = tracing::field::Empty,
+                        url.template = tracing::field::Empty,
+                        http.request.id = tracing::field::Empty,
+                        network.peer.address = tracing::field::Empty,
+                        network.peer.port = tracing::field::Empty,
+                        otel.library.name = $crate::reqwest_otel_span_macro::private::LIBRARY_NAME,
+                        otel.library.version = $crate::reqwest_otel_span_macro::private::LIBRARY_VERSION,
                         $($field)*
                     )
                 }
@@ -161,12 +240,21 @@ macro_rules! reqwest_otel_span {
                         server.address = %host,
                         server.port = %host_port,
                         user_agent.original = %user_agent,
-                        otel.kind = "client",
+                        otel.kind = tracing::field::Empty,
                         otel.name = %otel_name,
                         otel.status_code = tracing::field::Empty,
                         http.response.status_code = tracing::field::Empty,
                         error.message = tracing::field::Empty,
                         error.cause_chain = tracing::field::Empty,
+                        error.type = tracing::field::Empty,
+                        http.request.resend_count = tracing::field::Empty,
+                        http.client.duration = tracing::field::Empty,
+                        url.template = tracing::field::Empty,
+                        http.request.id = tracing::field::Empty,
+                        network.peer.address = tracing::field::Empty,
+                        network.peer.port = tracing::field::Empty,
+                        otel.library.name = $crate::reqwest_otel_span_macro::private::LIBRARY_NAME,
+                        otel.library.version = $crate::reqwest_otel_span_macro::private::LIBRARY_VERSION,
                         // old attributes
                         http.method = %method,
                         http.scheme = %scheme,
@@ -201,8 +289,300 @@ macro_rules! reqwest_otel_span {
     }
 }
 
+/// Rejects the custom fields of [`reqwest_otel_span!`](crate::reqwest_otel_span) that clash with
+/// one of its default fields.
+///
+/// Fields using a syntax it doesn't recognise are left for [`tracing::span!`] to validate.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __reqwest_otel_span_check_fields {
+    (@reserved $name:literal) => {
+        compile_error!(concat!(
+            "`", $name, "` is a default field of `reqwest_otel_span!` and can't be set again"
+        ));
+    };
+    (@name http.request.method) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.request.method") };
+    (@name url.scheme) => { $crate::__reqwest_otel_span_check_fields!(@reserved "url.scheme") };
+    (@name server.address) => { $crate::__reqwest_otel_span_check_fields!(@reserved "server.address") };
+    (@name server.port) => { $crate::__reqwest_otel_span_check_fields!(@reserved "server.port") };
+    (@name user_agent.original) => { $crate::__reqwest_otel_span_check_fields!(@reserved "user_agent.original") };
+    (@name otel.kind) => { $crate::__reqwest_otel_span_check_fields!(@reserved "otel.kind") };
+    (@name otel.name) => { $crate::__reqwest_otel_span_check_fields!(@reserved "otel.name") };
+    (@name otel.status_code) => { $crate::__reqwest_otel_span_check_fields!(@reserved "otel.status_code") };
+    (@name http.response.status_code) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.response.status_code") };
+    (@name error.message) => { $crate::__reqwest_otel_span_check_fields!(@reserved "error.message") };
+    (@name error.cause_chain) => { $crate::__reqwest_otel_span_check_fields!(@reserved "error.cause_chain") };
+    (@name error.type) => { $crate::__reqwest_otel_span_check_fields!(@reserved "error.type") };
+    (@name http.request.resend_count) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.request.resend_count") };
+    (@name http.client.duration) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.client.duration") };
+    (@name url.template) => { $crate::__reqwest_otel_span_check_fields!(@reserved "url.template") };
+    (@name http.request.id) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.request.id") };
+    (@name network.peer.address) => { $crate::__reqwest_otel_span_check_fields!(@reserved "network.peer.address") };
+    (@name network.peer.port) => { $crate::__reqwest_otel_span_check_fields!(@reserved "network.peer.port") };
+    (@name otel.library.name) => { $crate::__reqwest_otel_span_check_fields!(@reserved "otel.library.name") };
+    (@name otel.library.version) => { $crate::__reqwest_otel_span_check_fields!(@reserved "otel.library.version") };
+    // Added with the `deprecated_attributes` feature, reserved whether it is enabled or not
+    (@name http.method) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.method") };
+    (@name http.scheme) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.scheme") };
+    (@name http.host) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.host") };
+    (@name net.host.port) => { $crate::__reqwest_otel_span_check_fields!(@reserved "net.host.port") };
+    (@name http.user_agent) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.user_agent") };
+    (@name http.status_code) => { $crate::__reqwest_otel_span_check_fields!(@reserved "http.status_code") };
+    (@name $($name:tt)*) => {};
+    () => {};
+    ($($k:ident).+ = % $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_check_fields!(@name $($k).+);
+        $crate::__reqwest_otel_span_check_fields!($($($rest)*)?);
+    };
+    ($($k:ident).+ = ? $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_check_fields!(@name $($k).+);
+        $crate::__reqwest_otel_span_check_fields!($($($rest)*)?);
+    };
+    ($($k:ident).+ = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_check_fields!(@name $($k).+);
+        $crate::__reqwest_otel_span_check_fields!($($($rest)*)?);
+    };
+    (% $($k:ident).+ $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_check_fields!(@name $($k).+);
+        $crate::__reqwest_otel_span_check_fields!($($($rest)*)?);
+    };
+    (? $($k:ident).+ $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_check_fields!(@name $($k).+);
+        $crate::__reqwest_otel_span_check_fields!($($($rest)*)?);
+    };
+    ($($k:ident).+ $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_check_fields!(@name $($k).+);
+        $crate::__reqwest_otel_span_check_fields!($($($rest)*)?);
+    };
+    ($($unknown:tt)*) => {};
+}
+
+/// Looks for a `url_path` field value in the custom fields of
+/// [`reqwest_otel_span!`](crate::reqwest_otel_span) to declare the binding with.
+///
+/// Macro hygiene hides the variables declared by a macro from the tokens it is given, declaring
+/// the binding with the caller's own `url_path` token makes it visible to the fields. The fields are
+/// walked one at a time, like in [`__reqwest_otel_span_check_fields!`], rather than token by
+/// token, to stay well within the recursion limit.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __reqwest_otel_span_bind_fields {
+    (@check [$level:expr, $name:expr, $request:ident, $($field:tt)*] $found:ident [url_path] $rest:tt) => {
+        $crate::reqwest_otel_span!(@bound $found, level=$level, name=$name, $request, $($field)*)
+    };
+    (@check $ctx:tt $found:ident [$other:ident] [$($rest:tt)*]) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($rest)*)
+    };
+    (@find $ctx:tt $($k:ident).+ = % $value:ident $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@check $ctx $value [$value] [$($($rest)*)?])
+    };
+    (@find $ctx:tt $($k:ident).+ = ? $value:ident $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@check $ctx $value [$value] [$($($rest)*)?])
+    };
+    (@find $ctx:tt $($k:ident).+ = $value:ident $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@check $ctx $value [$value] [$($($rest)*)?])
+    };
+    (@find $ctx:tt $($k:ident).+ = % $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($($rest)*)?)
+    };
+    (@find $ctx:tt $($k:ident).+ = ? $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($($rest)*)?)
+    };
+    (@find $ctx:tt $($k:ident).+ = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($($rest)*)?)
+    };
+    (@find $ctx:tt % $k:ident $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@check $ctx $k [$k] [$($($rest)*)?])
+    };
+    (@find $ctx:tt ? $k:ident $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@check $ctx $k [$k] [$($($rest)*)?])
+    };
+    (@find $ctx:tt $k:ident $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@check $ctx $k [$k] [$($($rest)*)?])
+    };
+    (@find $ctx:tt % $($k:ident).+ $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($($rest)*)?)
+    };
+    (@find $ctx:tt ? $($k:ident).+ $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($($rest)*)?)
+    };
+    (@find $ctx:tt $($k:ident).+ $(, $($rest:tt)*)?) => {
+        $crate::__reqwest_otel_span_bind_fields!(@find $ctx $($($rest)*)?)
+    };
+    // No `url_path` value, or a syntax left for `tracing::span!` to validate: nothing can refer to
+    // the binding, declare it with our own token
+    ([$level:expr, $name:expr, $request:ident, $($field:tt)*] $($unknown:tt)*) => {
+        $crate::reqwest_otel_span!(@bound url_path, level=$level, name=$name, $request, $($field)*)
+    };
+    (@find $ctx:tt $($unknown:tt)*) => {
+        $crate::__reqwest_otel_span_bind_fields!($ctx)
+    };
+}
+
 #[doc(hidden)]
 pub mod private {
     #[doc(hidden)]
     pub use tracing::{span, Level};
+
+    /// The `otel.library.name` of the spans created by the macro.
+    #[doc(hidden)]
+    pub const LIBRARY_NAME: &str = env!("CARGO_PKG_NAME");
+
+    /// The `otel.library.version` of the spans created by the macro, the version of this crate
+    /// rather than the one of the crate calling the macro.
+    #[doc(hidden)]
+    pub const LIBRARY_VERSION: &str = env!("CARGO_PKG_VERSION");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
+
+    /// Records the fields of every new span, formatted with `Debug`.
+    #[derive(Clone, Default)]
+    struct FieldsRecorder(Arc<Mutex<HashMap<String, String>>>);
+
+    impl Visit for FieldsRecorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for FieldsRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut self.clone());
+        }
+    }
+
+    #[test]
+    fn accepts_display_and_debug_wrappers() {
+        let recorder = FieldsRecorder::default();
+        let subscriber = Registry::default().with(recorder.clone());
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            "https://example.com/".parse().unwrap(),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = reqwest_otel_span!(
+                name = "test",
+                request,
+                displayed = tracing::field::display("some value"),
+                debugged = tracing::field::debug(Some(42)),
+            );
+        });
+
+        let fields = recorder.0.lock().unwrap();
+        assert_eq!(fields["displayed"], "some value");
+        assert_eq!(fields["debugged"], "Some(42)");
+    }
+
+    #[test]
+    fn exposes_the_url_path_to_the_fields() {
+        let recorder = FieldsRecorder::default();
+        let subscriber = Registry::default().with(recorder.clone());
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            "https://example.com/payment/123?q=1".parse().unwrap(),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = reqwest_otel_span!(
+                name = "test",
+                request,
+                displayed = tracing::field::display("some value"),
+                my_path = url_path,
+                displayed_path = %url_path,
+                url_path,
+            );
+            let _span = reqwest_otel_span!(name = "test", request, ?url_path);
+        });
+
+        let fields = recorder.0.lock().unwrap();
+        assert_eq!(fields["my_path"], r#""/payment/123""#);
+        assert_eq!(fields["displayed_path"], "/payment/123");
+        assert_eq!(fields["url_path"], r#""/payment/123""#);
+    }
+
+    #[test]
+    fn records_the_library_name_and_version() {
+        let recorder = FieldsRecorder::default();
+        let subscriber = Registry::default().with(recorder.clone());
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            "https://example.com/".parse().unwrap(),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = reqwest_otel_span!(name = "test", request);
+        });
+
+        let manifest_version = include_str!("../Cargo.toml")
+            .lines()
+            .find_map(|line| line.strip_prefix("version = "))
+            .unwrap()
+            .trim_matches('"');
+        let fields = recorder.0.lock().unwrap();
+        assert_eq!(fields[crate::OTEL_LIBRARY_NAME], r#""reqwest-tracing""#);
+        assert_eq!(
+            fields[crate::OTEL_LIBRARY_VERSION],
+            format!("{:?}", manifest_version)
+        );
+    }
+
+    #[test]
+    fn accepts_level_before_or_after_name() {
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            "https://example.com/".parse().unwrap(),
+        );
+        let record = |make_span: &dyn Fn() -> tracing::Span| {
+            let recorder = FieldsRecorder::default();
+            let subscriber = Registry::default().with(recorder.clone());
+            let metadata = tracing::subscriber::with_default(subscriber, || {
+                let span = make_span();
+                let metadata = span.metadata().unwrap();
+                (metadata.name(), *metadata.level())
+            });
+            let fields = recorder.0.lock().unwrap().clone();
+            (metadata, fields)
+        };
+
+        let level_first = record(&|| {
+            reqwest_otel_span!(
+                level = tracing::Level::DEBUG,
+                name = "test",
+                request,
+                extra = 1
+            )
+        });
+        let name_first = record(&|| {
+            reqwest_otel_span!(
+                name = "test",
+                level = tracing::Level::DEBUG,
+                request,
+                extra = 1
+            )
+        });
+        let name_first_no_fields =
+            record(&|| reqwest_otel_span!(name = "test", level = tracing::Level::DEBUG, request));
+
+        assert_eq!(level_first, name_first);
+        assert_eq!(level_first.0 .1, tracing::Level::DEBUG);
+        assert_eq!(level_first.1["otel.name"], "test");
+        assert_eq!(level_first.1["extra"], "1");
+        assert_eq!(name_first_no_fields.0, level_first.0);
+        assert!(!name_first_no_fields.1.contains_key("extra"));
+    }
 }